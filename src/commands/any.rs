@@ -0,0 +1,193 @@
+//! Implements enums that wrap every command and response, for sending commands that are chosen at runtime
+
+use super::*;
+
+/// Any command that can be sent to the V5
+///
+/// Each command is its own type, so a command chosen at runtime (such as from user input in a CLI) can not be stored
+/// in a single variable. This wraps every command so that it can be, and is sent with `send_any` on
+/// [crate::devices::device::Device] or [crate::devices::asyncdevice::AsyncDevice], which returns the matching [AnyResponse].
+///
+/// # Examples
+///
+/// ```rust
+/// use vexv5_serial::commands::{AnyCommand, KVRead, GetSystemFlags};
+///
+/// // Choose the command at runtime
+/// let input = "flags";
+/// let command = match input {
+///     "flags" => AnyCommand::GetSystemFlags(GetSystemFlags()),
+///     _ => AnyCommand::KVRead(KVRead("teamnumber")),
+/// };
+///
+/// assert!(matches!(command, AnyCommand::GetSystemFlags(_)));
+/// ```
+#[derive(Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum AnyCommand<'a> {
+    Simple(Simple<'a>),
+    Extended(Extended<'a>),
+    GetSystemVersion(GetSystemVersion),
+    GetSystemStatus(GetSystemStatus),
+    GetSystemFlags(GetSystemFlags),
+    SwitchChannel(SwitchChannel),
+    KVRead(KVRead<'a>),
+    KVWrite(KVWrite<'a>),
+    FileTransferInit(FileTransferInit),
+    FileTransferExit(FileTransferExit),
+    FileTransferSetLink(FileTransferSetLink),
+    FileTransferWrite(FileTransferWrite<'a>),
+    FileTransferRead(FileTransferRead),
+    GetFileMetadataByName(GetFileMetadataByName<'a>),
+    GetDirectoryCount(GetDirectoryCount),
+    GetFileMetadataByIndex(GetFileMetadataByIndex),
+    EraseFile(EraseFile),
+    ExecuteFile(ExecuteFile),
+}
+
+impl<'a> AnyCommand<'a> {
+    /// Encodes the wrapped command, the same way as [Command::encode_request]
+    pub fn encode_request(self) -> Result<(u8, Vec<u8>), crate::errors::DecodeError> {
+        match self {
+            AnyCommand::Simple(c) => c.encode_request(),
+            AnyCommand::Extended(c) => c.encode_request(),
+            AnyCommand::GetSystemVersion(c) => c.encode_request(),
+            AnyCommand::GetSystemStatus(c) => c.encode_request(),
+            AnyCommand::GetSystemFlags(c) => c.encode_request(),
+            AnyCommand::SwitchChannel(c) => c.encode_request(),
+            AnyCommand::KVRead(c) => c.encode_request(),
+            AnyCommand::KVWrite(c) => c.encode_request(),
+            AnyCommand::FileTransferInit(c) => c.encode_request(),
+            AnyCommand::FileTransferExit(c) => c.encode_request(),
+            AnyCommand::FileTransferSetLink(c) => c.encode_request(),
+            AnyCommand::FileTransferWrite(c) => c.encode_request(),
+            AnyCommand::FileTransferRead(c) => c.encode_request(),
+            AnyCommand::GetFileMetadataByName(c) => c.encode_request(),
+            AnyCommand::GetDirectoryCount(c) => c.encode_request(),
+            AnyCommand::GetFileMetadataByIndex(c) => c.encode_request(),
+            AnyCommand::EraseFile(c) => c.encode_request(),
+            AnyCommand::ExecuteFile(c) => c.encode_request(),
+        }
+    }
+
+    /// Decodes a response to the wrapped command into the matching [AnyResponse] variant
+    /// 
+    /// Unlike [Command::decode_response], this needs the command, since the command decides which decoder is used.
+    pub fn decode_response(&self, command_id: u8, data: Vec<u8>) -> Result<AnyResponse, crate::errors::DecodeError> {
+        Ok(match self {
+            AnyCommand::Simple(_) => AnyResponse::Simple(Simple::decode_response(command_id, data)?),
+            AnyCommand::Extended(_) => AnyResponse::Extended(Extended::decode_response(command_id, data)?),
+            AnyCommand::GetSystemVersion(_) => AnyResponse::GetSystemVersion(GetSystemVersion::decode_response(command_id, data)?),
+            AnyCommand::GetSystemStatus(_) => AnyResponse::GetSystemStatus(GetSystemStatus::decode_response(command_id, data)?),
+            AnyCommand::GetSystemFlags(_) => AnyResponse::GetSystemFlags(GetSystemFlags::decode_response(command_id, data)?),
+            AnyCommand::SwitchChannel(_) => {
+                SwitchChannel::decode_response(command_id, data)?;
+                AnyResponse::SwitchChannel
+            }
+            AnyCommand::KVRead(_) => AnyResponse::KVRead(KVRead::decode_response(command_id, data)?),
+            AnyCommand::KVWrite(_) => {
+                KVWrite::decode_response(command_id, data)?;
+                AnyResponse::KVWrite
+            }
+            AnyCommand::FileTransferInit(_) => AnyResponse::FileTransferInit(FileTransferInit::decode_response(command_id, data)?),
+            AnyCommand::FileTransferExit(_) => {
+                FileTransferExit::decode_response(command_id, data)?;
+                AnyResponse::FileTransferExit
+            }
+            AnyCommand::FileTransferSetLink(_) => {
+                FileTransferSetLink::decode_response(command_id, data)?;
+                AnyResponse::FileTransferSetLink
+            }
+            AnyCommand::FileTransferWrite(_) => {
+                FileTransferWrite::decode_response(command_id, data)?;
+                AnyResponse::FileTransferWrite
+            }
+            AnyCommand::FileTransferRead(_) => AnyResponse::FileTransferRead(FileTransferRead::decode_response(command_id, data)?),
+            AnyCommand::GetFileMetadataByName(_) => AnyResponse::GetFileMetadataByName(GetFileMetadataByName::decode_response(command_id, data)?),
+            AnyCommand::GetDirectoryCount(_) => AnyResponse::GetDirectoryCount(GetDirectoryCount::decode_response(command_id, data)?),
+            AnyCommand::GetFileMetadataByIndex(_) => AnyResponse::GetFileMetadataByIndex(GetFileMetadataByIndex::decode_response(command_id, data)?),
+            AnyCommand::EraseFile(_) => {
+                EraseFile::decode_response(command_id, data)?;
+                AnyResponse::EraseFile
+            }
+            AnyCommand::ExecuteFile(_) => {
+                ExecuteFile::decode_response(command_id, data)?;
+                AnyResponse::ExecuteFile
+            }
+        })
+    }
+
+    /// Returns whether the device will send a response to the wrapped command, the same way as [Command::expects_response]
+    pub fn expects_response(&self) -> bool {
+        match self {
+            AnyCommand::Simple(c) => c.expects_response(),
+            AnyCommand::Extended(c) => c.expects_response(),
+            AnyCommand::GetSystemVersion(c) => c.expects_response(),
+            AnyCommand::GetSystemStatus(c) => c.expects_response(),
+            AnyCommand::GetSystemFlags(c) => c.expects_response(),
+            AnyCommand::SwitchChannel(c) => c.expects_response(),
+            AnyCommand::KVRead(c) => c.expects_response(),
+            AnyCommand::KVWrite(c) => c.expects_response(),
+            AnyCommand::FileTransferInit(c) => c.expects_response(),
+            AnyCommand::FileTransferExit(c) => c.expects_response(),
+            AnyCommand::FileTransferSetLink(c) => c.expects_response(),
+            AnyCommand::FileTransferWrite(c) => c.expects_response(),
+            AnyCommand::FileTransferRead(c) => c.expects_response(),
+            AnyCommand::GetFileMetadataByName(c) => c.expects_response(),
+            AnyCommand::GetDirectoryCount(c) => c.expects_response(),
+            AnyCommand::GetFileMetadataByIndex(c) => c.expects_response(),
+            AnyCommand::EraseFile(c) => c.expects_response(),
+            AnyCommand::ExecuteFile(c) => c.expects_response(),
+        }
+    }
+
+    /// Returns whether the wrapped command changes the state of the device, the same way as [Command::modifies_device]
+    pub fn modifies_device(&self) -> bool {
+        match self {
+            AnyCommand::Simple(c) => c.modifies_device(),
+            AnyCommand::Extended(c) => c.modifies_device(),
+            AnyCommand::GetSystemVersion(c) => c.modifies_device(),
+            AnyCommand::GetSystemStatus(c) => c.modifies_device(),
+            AnyCommand::GetSystemFlags(c) => c.modifies_device(),
+            AnyCommand::SwitchChannel(c) => c.modifies_device(),
+            AnyCommand::KVRead(c) => c.modifies_device(),
+            AnyCommand::KVWrite(c) => c.modifies_device(),
+            AnyCommand::FileTransferInit(c) => c.modifies_device(),
+            AnyCommand::FileTransferExit(c) => c.modifies_device(),
+            AnyCommand::FileTransferSetLink(c) => c.modifies_device(),
+            AnyCommand::FileTransferWrite(c) => c.modifies_device(),
+            AnyCommand::FileTransferRead(c) => c.modifies_device(),
+            AnyCommand::GetFileMetadataByName(c) => c.modifies_device(),
+            AnyCommand::GetDirectoryCount(c) => c.modifies_device(),
+            AnyCommand::GetFileMetadataByIndex(c) => c.modifies_device(),
+            AnyCommand::EraseFile(c) => c.modifies_device(),
+            AnyCommand::ExecuteFile(c) => c.modifies_device(),
+        }
+    }
+}
+
+/// The response to an [AnyCommand]
+///
+/// Each variant is the response to the [AnyCommand] variant with the same name.
+/// Commands that respond with no data have a variant with no fields.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AnyResponse {
+    Simple(SimpleResponse),
+    Extended(ExtendedResponse),
+    GetSystemVersion(V5SystemVersion),
+    GetSystemStatus(V5SystemStatus),
+    GetSystemFlags(V5SystemFlags),
+    SwitchChannel,
+    KVRead(String),
+    KVWrite,
+    FileTransferInit(FileTransferInitResponse),
+    FileTransferExit,
+    FileTransferSetLink,
+    FileTransferWrite,
+    FileTransferRead(Vec<u8>),
+    GetFileMetadataByName(crate::v5::FileMetadataByName),
+    GetDirectoryCount(i16),
+    GetFileMetadataByIndex(crate::v5::FileMetadataByIndex),
+    EraseFile,
+    ExecuteFile,
+}
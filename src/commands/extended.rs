@@ -15,11 +15,86 @@ use super::Command;
 /// * `1` - The payload of the extended command as a reference to a slice of [u8]s
 /// 
 /// # Examples
-/// No examples are provided here. For implementation details, see a basic command such as `KVRead` to see how this can be used.
+/// For implementation details, see a basic command such as `KVRead` to see how this can be used.
+/// 
+/// ```rust
+/// use vexv5_serial::commands::{Command, Extended};
+/// 
+/// // The header, the simple command id (0x56), the extended command id, the length, the payload, then the big endian CRC16
+/// assert_eq!(Extended(0x22, &[]).encode_request().unwrap(), (0x56, vec![
+///     0xc9, 0x36, 0xb8, 0x47, 0x56, 0x22, 0x00, 0x60, 0xfc,
+/// ]));
+/// 
+/// // Lengths below 0x80 are sent as one byte. Lengths of 0x80 and up are sent as two big endian bytes,
+/// // with the top bit of the first byte set.
+/// assert_eq!(&Extended(0x14, &[0; 127]).encode_request().unwrap().1[6..7], &[0x7f]);
+/// assert_eq!(&Extended(0x14, &[0; 128]).encode_request().unwrap().1[6..8], &[0x80, 0x80]);
+/// assert_eq!(&Extended(0x14, &[0; 129]).encode_request().unwrap().1[6..8], &[0x80, 0x81]);
+/// 
+/// // File transfer writes (0x13) that are not padded to four bytes are rejected before they are sent,
+/// // since the brain would NACK them. FileTransferWrite pads its data automatically.
+/// assert!(Extended(0x13, &[0; 7]).encode_request().is_err());
+/// ```
 #[derive(Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Extended<'a>(pub u8, pub &'a[u8]);
 
 impl<'a> Extended<'a> {
+    /// Decodes a whole extended response packet, from the `AA 55` header to the last byte of the CRC16
+    /// 
+    /// This is for packets that were not recieved by a device, such as ones captured with a packet tracer or read from a log.
+    /// The declared length is read from the packet's header and compared with the number of bytes after it, so a truncated
+    /// or overlong packet fails with [crate::errors::DecodeError::PacketLengthMismatch] instead of decoding a garbage payload.
+    /// 
+    /// # Examples
+    /// 
+    /// ```rust
+    /// use vexv5_serial::checks::VexExtPacketChecks;
+    /// use vexv5_serial::commands::Extended;
+    /// use vexv5_serial::errors::DecodeError;
+    /// 
+    /// let packet = [0xaa, 0x55, 0x56, 0x0b, 0x20, 0x76, 0x00, 0x00, 0x00, 0x00, 0xa5, 0x80, 0x01, 0x2c, 0x93];
+    /// assert_eq!(Extended::decode_extended_packet(&packet, VexExtPacketChecks::ALL).unwrap().0, 0x20);
+    /// 
+    /// // The header declares 11 bytes, but only 10 follow it
+    /// assert!(matches!(
+    ///     Extended::decode_extended_packet(&packet[..14], VexExtPacketChecks::ALL),
+    ///     Err(DecodeError::PacketLengthMismatch(11, 10))
+    /// ));
+    /// ```
+    pub fn decode_extended_packet(packet: &[u8], checks: VexExtPacketChecks) -> Result<ExtendedResponse, crate::errors::DecodeError> {
+        // The header, the simple command id, and at least one length byte
+        let (header, rest) = match packet.split_first_chunk::<4>() {
+            Some(v) => v,
+            None => return Err(crate::errors::DecodeError::PacketLengthError),
+        };
+
+        if header[..2] != [0xaa, 0x55] {
+            return Err(crate::errors::DecodeError::InvalidValue("packet does not start with the response header".to_string()));
+        }
+
+        let command_id = header[2];
+        if command_id != 0x56 {
+            return Err(crate::errors::DecodeError::ExpectedExtended);
+        }
+
+        // Lengths of 0x80 and up take two bytes, with the top bit of the first byte set
+        let (declared, data) = if header[3] & 0x80 == 0x80 {
+            match rest.split_first() {
+                Some((low, data)) => ((((header[3] & 0x7f) as usize) << 8) | *low as usize, data),
+                None => return Err(crate::errors::DecodeError::PacketLengthError),
+            }
+        } else {
+            (header[3] as usize, rest)
+        };
+
+        if declared != data.len() {
+            return Err(crate::errors::DecodeError::PacketLengthMismatch(declared, data.len()));
+        }
+
+        Self::decode_extended(command_id, data.to_vec(), checks)
+    }
+
     /// Decodes an extended payload from a stream
     pub fn decode_extended(command_id: u8, data: Vec<u8>, checks: VexExtPacketChecks) -> Result<ExtendedResponse, crate::errors::DecodeError> {
 
@@ -31,6 +106,13 @@ impl<'a> Extended<'a> {
             return Err(crate::errors::DecodeError::ExpectedExtended);
         }
 
+        // `data` is taken to be exactly the declared length, as it is when it comes from a device.
+        // Packets from anywhere else should go through decode_extended_packet, which compares the declared length first.
+        // The data has to be long enough to hold the command id, the ACK, and the CRC, or the payload boundaries below are meaningless.
+        if checks.contains(VexExtPacketChecks::LENGTH) && packet.1.len() < 4 {
+            return Err(crate::errors::DecodeError::PacketLengthError);
+        }
+
         // Get the command id
         let command_id = match packet.1.first() {
             Some(v) => *v,
@@ -52,7 +134,7 @@ impl<'a> Extended<'a> {
         }
 
         // Get the final payload value, removing the last two CRC bytes
-        let payload = match packet.1.get(2..packet.1.len()-2) {
+        let payload = match packet.1.len().checked_sub(2).and_then(|end| packet.1.get(2..end)) {
             Some(v) => v,
             None => return Err(crate::errors::DecodeError::PacketLengthError)
         }.to_vec();
@@ -70,13 +152,25 @@ impl<'a> Command for Extended<'a> {
         // Create the empty extended packet, with the extended command ID
         let mut packet = vec![self.0];
 
+        // File transfer writes (0x13) must be padded to four bytes, or the brain will NACK them with NACKLengthNotPaddedTo4.
+        // The payload is a four byte address followed by the data, so checking the whole payload checks the data.
+        if self.0 == 0x13 && self.1.len() % 4 != 0 {
+            return Err(crate::errors::DecodeError::InvalidValue(format!("file transfer write payload of {} bytes is not padded to 4 bytes", self.1.len())));
+        }
+
+        // The length can be at most two bytes, with the top bit used as a flag, so it must fit in 15 bits
+        if self.1.len() > 0x7fff {
+            return Err(crate::errors::DecodeError::InvalidValue("extended payload longer than 0x7fff bytes".to_string()));
+        }
+
         // Get the length of the payload
         let payload_length = self.1.len() as u16;
 
-        // If the payload is larger than 0x80, then we need to push the high byte separately
+        // If the payload does not fit in 7 bits, then we need to push the high byte separately, with the top bit set.
         // This appears to be a primitive varint implementation. We will do what PROS cli
-        // does and max out at two bytes
-        if payload_length > 0x80 {
+        // does and max out at two bytes. A single length byte of 0x80 or more would have its top bit
+        // read as the flag, so 0x80 itself needs two bytes.
+        if payload_length >= 0x80 {
             packet.push(((payload_length >> 8) | 0x80) as u8);
         }
 
@@ -116,6 +210,16 @@ impl<'a> Command for Extended<'a> {
 
     
     
+
+    /// Raw extended commands are checked by their id, so that they can not be used to get around read-only mode.
+    /// Transfer inits (0x11) only modify the device if they begin an upload.
+    fn modifies_device(&self) -> bool {
+        match self.0 {
+            0x11 => self.1.first() == Some(&(crate::v5::FileTransferFunction::Upload as u8)),
+            0x13 | 0x15 | 0x18 | 0x1B | 0x2f => true,
+            _ => false,
+        }
+    }
 }
 
 /// The response returned by an extended command
@@ -124,4 +228,5 @@ impl<'a> Command for Extended<'a> {
 /// 
 /// * `0` - The command id of the recieved response as a [u8]
 /// * `1` - The payload of the recieved response as a [`Vec<u8>`]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ExtendedResponse(pub u8, pub Vec<u8>);
\ No newline at end of file
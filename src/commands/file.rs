@@ -3,14 +3,99 @@ use crate::{v5::{
     FileTransferTarget,
     FileTransferVID,
     FileTransferOptions,
-    FileTransferType, FileTransferComplete, FileMetadataByName
+    FileTransferType, FileTransferComplete, FileMetadataByName, FileMetadataByIndex
 }, checks::VexExtPacketChecks};
 
 use super::Command;
 
 
+/// Encodes a file name into the 24 byte, null-padded name field used by file commands.
+/// 
+/// Returns [crate::errors::DecodeError::InvalidValue] if the name is not ascii or is longer than 23 characters,
+/// rather than truncating it and operating on the wrong file. The last byte of the field is always left as a null terminator,
+/// so that the brain never reads past the end of the name.
+/// 
+/// # Examples
+/// 
+/// ```rust
+/// use vexv5_serial::commands::encode_file_name;
+/// 
+/// let name = encode_file_name("slot_1.bin").unwrap();
+/// assert_eq!(&name[..11], b"slot_1.bin\0");
+/// 
+/// assert!(encode_file_name("not ascii \u{1F916}").is_err());
+/// 
+/// // There must be room for the null terminator
+/// assert!(encode_file_name("twenty_three_characters").is_ok());
+/// assert!(encode_file_name("twenty_four_characters!!").is_err());
+/// ```
+pub fn encode_file_name(name: &str) -> Result<[u8; 24], crate::errors::DecodeError> {
+    // Only ascii names can be sent to the brain
+    if !name.is_ascii() {
+        return Err(crate::errors::DecodeError::InvalidValue("name not ASCII".to_string()));
+    }
+
+    // Names longer than the field (minus the null terminator) would be truncated
+    if name.len() > 23 {
+        return Err(crate::errors::DecodeError::InvalidValue("name longer than 23 characters".to_string()));
+    }
+
+    // Copy the name into the null-padded field
+    let mut encoded = [0u8; 24];
+    encoded[..name.len()].copy_from_slice(name.as_bytes());
+
+    Ok(encoded)
+}
+
+/// Decodes a 24 byte, null-padded name field into a string.
+/// 
+/// The name ends at the first null byte, and any bytes that are not valid utf8 are replaced.
+pub fn decode_file_name(name: &[u8; 24]) -> String {
+    // Find the null terminator, or use the whole field if there is none
+    let end = name.iter().position(|b| *b == 0).unwrap_or(name.len());
+
+    String::from_utf8_lossy(&name[..end]).trim().to_string()
+}
+
+
 /// Initializes a file transfer between the brain and host
+/// 
+/// The options field is a single byte on the wire, packed after the function, target, and vid bytes.
+/// The function and target have their own bytes, so no option bits are derived from them, and `options` is sent exactly as given.
+/// The only bit that affects an init is [FileTransferOptions::OVERWRITE], which must be set when uploading over a file that already exists.
+/// 
+/// # Examples
+/// 
+/// ```rust
+/// use vexv5_serial::commands::{Command, FileTransferInit, encode_file_name};
+/// use vexv5_serial::v5::*;
+/// 
+/// let init = FileTransferInit {
+///     function: FileTransferFunction::Upload,
+///     target: FileTransferTarget::Flash,
+///     vid: FileTransferVID::User,
+///     options: FileTransferOptions::OVERWRITE,
+///     file_type: FileTransferType::Bin,
+///     length: 4,
+///     addr: 0x03800000,
+///     crc: 0x12345678,
+///     timestamp: 0,
+///     version: 0x01000000,
+///     name: encode_file_name("slot_1.bin").unwrap(),
+/// };
+/// 
+/// // The length, address, crc, timestamp, and version are all little endian
+/// assert_eq!(init.encode_request().unwrap(), (0x56, vec![
+///     0xc9, 0x36, 0xb8, 0x47, 0x56, 0x11, 0x34, 0x01, 0x01, 0x01, 0x01, 0x04,
+///     0x00, 0x00, 0x00, 0x00, 0x00, 0x80, 0x03, 0x78, 0x56, 0x34, 0x12, 0x62,
+///     0x69, 0x6e, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x73,
+///     0x6c, 0x6f, 0x74, 0x5f, 0x31, 0x2e, 0x62, 0x69, 0x6e, 0x00, 0x00, 0x00,
+///     0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x0b,
+///     0x0c,
+/// ]));
+/// ```
 #[derive(Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FileTransferInit {
     pub function: FileTransferFunction,
     pub target: FileTransferTarget,
@@ -25,11 +110,39 @@ pub struct FileTransferInit {
     pub name: [u8; 24]
 }
 
+impl FileTransferInit {
+    /// Returns the default address to transfer to or from for a target and VID.
+    /// 
+    /// * [FileTransferTarget::Flash] - `0x03800000`, where user programs are loaded. This is the same for every VID.
+    /// * [FileTransferTarget::Screen] - `0`, the start of the screen buffer that screen captures are read from.
+    /// 
+    /// # Examples
+    /// 
+    /// ```rust
+    /// use vexv5_serial::commands::FileTransferInit;
+    /// use vexv5_serial::v5::{FileTransferTarget, FileTransferVID};
+    /// 
+    /// assert_eq!(FileTransferInit::default_addr(FileTransferTarget::Flash, FileTransferVID::User), 0x03800000);
+    /// assert_eq!(FileTransferInit::default_addr(FileTransferTarget::Screen, FileTransferVID::System), 0);
+    /// ```
+    pub fn default_addr(target: FileTransferTarget, vid: FileTransferVID) -> u32 {
+        match (target, vid) {
+            (FileTransferTarget::Flash, _) => 0x03800000,
+            (FileTransferTarget::Screen, _) => 0,
+        }
+    }
+}
+
 impl Command for FileTransferInit {
     type Response = FileTransferInitResponse;
 
     fn encode_request(self) -> Result<(u8, Vec<u8>), crate::errors::DecodeError> {
         
+        // The name must be null terminated within the field, or the brain will read past it
+        if !self.name.contains(&0) {
+            return Err(crate::errors::DecodeError::InvalidValue("name is not null terminated".to_string()));
+        }
+
         // Create the empty payload
         let mut payload = Vec::<u8>::new();
 
@@ -92,9 +205,14 @@ impl Command for FileTransferInit {
             crc
         })
     }
+
+    fn modifies_device(&self) -> bool {
+        self.function == crate::v5::FileTransferFunction::Upload
+    }
 }
 
 #[derive(Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FileTransferInitResponse {
     pub max_packet_size: u16,
     pub file_size: u32,
@@ -108,7 +226,19 @@ pub struct FileTransferInitResponse {
 /// # Members
 /// 
 /// * `0` - The action to complete when the transfer is finished
+/// 
+/// # Examples
+/// 
+/// ```rust
+/// use vexv5_serial::commands::{Command, FileTransferExit};
+/// use vexv5_serial::v5::FileTransferComplete;
+/// 
+/// assert_eq!(FileTransferExit(FileTransferComplete::RunProgram).encode_request().unwrap(), (0x56, vec![
+///     0xc9, 0x36, 0xb8, 0x47, 0x56, 0x12, 0x01, 0x01, 0x76, 0x13,
+/// ]));
+/// ```
 #[derive(Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FileTransferExit(pub FileTransferComplete);
 
 impl Command for FileTransferExit {
@@ -144,12 +274,36 @@ impl Command for FileTransferExit {
 
 /// Sets the linked file for the current transfer
 /// 
+/// This is sent after [FileTransferInit] and before any data is written. It links the file being uploaded to a file
+/// that must already be on the brain, such as a program binary to the library it was built against. It does not link
+/// a program's ini file to its binary; the brain pairs those by their `slot_N` names alone.
+/// 
+/// Like [FileTransferInit], no option bits are derived from the transfer, and `options` is sent exactly as given.
+/// They do not need to match the options the transfer was initialized with, and PROS-CLI always sends [FileTransferOptions::NONE].
+/// 
 /// # Members
 /// 
 /// * `0` - The linked file name
-/// * `1` - The file VID
-/// * `2` - The file options
+/// * `1` - The VID of the linked file
+/// * `2` - The file options, which are a single byte on the wire
+/// 
+/// # Examples
+/// 
+/// ```rust
+/// use vexv5_serial::commands::{Command, FileTransferSetLink, encode_file_name};
+/// use vexv5_serial::v5::{FileTransferVID, FileTransferOptions};
+/// 
+/// let link = FileTransferSetLink(encode_file_name("slot_1.bin").unwrap(), FileTransferVID::User, FileTransferOptions::NONE);
+/// 
+/// // The vid and options come before the name
+/// assert_eq!(link.encode_request().unwrap(), (0x56, vec![
+///     0xc9, 0x36, 0xb8, 0x47, 0x56, 0x15, 0x1a, 0x01, 0x00, 0x73, 0x6c, 0x6f,
+///     0x74, 0x5f, 0x31, 0x2e, 0x62, 0x69, 0x6e, 0x00, 0x00, 0x00, 0x00, 0x00,
+///     0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x79, 0xa3,
+/// ]));
+/// ```
 #[derive(Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FileTransferSetLink (pub [u8; 24], pub FileTransferVID, pub FileTransferOptions);
 
 impl Command for FileTransferSetLink {
@@ -184,6 +338,10 @@ impl Command for FileTransferSetLink {
         
         Ok(())
     }
+
+    fn modifies_device(&self) -> bool {
+        true
+    }
 }
 
 
@@ -194,7 +352,20 @@ impl Command for FileTransferSetLink {
 /// 
 /// * `0` - The address to read data from
 /// * `1` - The number of bytes to read, will be padded to 4 bytes
+/// 
+/// # Examples
+/// 
+/// ```rust
+/// use vexv5_serial::commands::{Command, FileTransferRead};
+/// 
+/// // The address and the (padded) length are little endian
+/// assert_eq!(FileTransferRead(0x03800000, 7).encode_request().unwrap(), (0x56, vec![
+///     0xc9, 0x36, 0xb8, 0x47, 0x56, 0x14, 0x06, 0x00, 0x00, 0x80, 0x03, 0x08,
+///     0x00, 0x81, 0x96,
+/// ]));
+/// ```
 #[derive(Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FileTransferRead(pub u32, pub u16);
 
 impl Command for FileTransferRead {
@@ -203,11 +374,8 @@ impl Command for FileTransferRead {
     fn encode_request(self) -> Result<(u8, Vec<u8>), crate::errors::DecodeError> {
         
         // Pad nbytes to a 4 byte barrier
-        let nbytes = if self.1 % 4 == 0 {
-            self.1
-        } else {
-            self.1 + 4 - (self.1 % 4)
-        };
+        let nbytes = u16::try_from(crate::align4(self.1 as usize))
+            .map_err(|_| crate::errors::DecodeError::InvalidValue(format!("read of {} bytes can not be padded to 4 bytes", self.1)))?;
 
         // Create the payload
         let mut payload = Vec::<u8>::new();
@@ -249,7 +417,20 @@ impl Command for FileTransferRead {
 /// 
 /// * `0` - The address to write at
 /// * `1` - The data to write
+/// 
+/// # Examples
+/// 
+/// ```rust
+/// use vexv5_serial::commands::{Command, FileTransferWrite};
+/// 
+/// // The address is little endian, and the data is padded with zeros to 4 bytes
+/// assert_eq!(FileTransferWrite(0x03800000, &[1, 2, 3]).encode_request().unwrap(), (0x56, vec![
+///     0xc9, 0x36, 0xb8, 0x47, 0x56, 0x13, 0x08, 0x00, 0x00, 0x80, 0x03, 0x01,
+///     0x02, 0x03, 0x00, 0x6c, 0x1f,
+/// ]));
+/// ```
 #[derive(Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct FileTransferWrite<'a>(pub u32, pub &'a[u8]);
 
 impl<'a> Command for FileTransferWrite<'a> {
@@ -262,11 +443,7 @@ impl<'a> Command for FileTransferWrite<'a> {
 
         // Pad the payload to 4 bytes
         let mut payload = self.1.to_vec();
-        payload.resize(if payload.len() % 4 == 0 {
-            payload.len()
-        } else {
-            payload.len() + 4 - (payload.len() % 4)
-        },0);
+        payload.resize(crate::align4(payload.len()), 0);
 
         // Add the address to the packet
         packet.extend(self.0.to_le_bytes());
@@ -291,6 +468,10 @@ impl<'a> Command for FileTransferWrite<'a> {
         // Return Ok
         Ok(())
     }
+
+    fn modifies_device(&self) -> bool {
+        true
+    }
 }
 
 
@@ -302,9 +483,25 @@ impl<'a> Command for FileTransferWrite<'a> {
 /// 
 /// * `0` - The name of the file
 /// * `1` - The VID of the file
-/// * `2` - The file transfer options -- Use NONE
+/// * `2` - The file transfer options, which are a single byte on the wire -- Use NONE
+/// 
+/// # Examples
+/// 
+/// ```rust
+/// use vexv5_serial::commands::{Command, GetFileMetadataByName, encode_file_name};
+/// use vexv5_serial::v5::{FileTransferVID, FileTransferOptions};
+/// 
+/// let name = encode_file_name("slot_1.bin").unwrap();
 /// 
+/// // The vid and options come before the name
+/// assert_eq!(GetFileMetadataByName(&name, FileTransferVID::User, FileTransferOptions::NONE).encode_request().unwrap(), (0x56, vec![
+///     0xc9, 0x36, 0xb8, 0x47, 0x56, 0x19, 0x1a, 0x01, 0x00, 0x73, 0x6c, 0x6f,
+///     0x74, 0x5f, 0x31, 0x2e, 0x62, 0x69, 0x6e, 0x00, 0x00, 0x00, 0x00, 0x00,
+///     0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xdf, 0x72,
+/// ]));
+/// ```
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct GetFileMetadataByName<'a>(pub &'a [u8; 24], pub FileTransferVID, pub FileTransferOptions);
 
 impl<'a> Command for GetFileMetadataByName<'a> {
@@ -352,4 +549,254 @@ impl<'a> Command for GetFileMetadataByName<'a> {
         // Return the data
         Ok(result)
     }
-}
\ No newline at end of file
+}
+
+/// Gets the number of files stored under a VID
+/// 
+/// # Members
+/// 
+/// * `0` - The VID to count the files of
+/// * `1` - The file transfer options, which are a single byte on the wire -- Use NONE
+/// 
+/// # Examples
+/// 
+/// ```rust
+/// use vexv5_serial::commands::{Command, GetDirectoryCount};
+/// use vexv5_serial::v5::{FileTransferVID, FileTransferOptions};
+/// 
+/// assert_eq!(GetDirectoryCount(FileTransferVID::User, FileTransferOptions::NONE).encode_request().unwrap(), (0x56, vec![
+///     0xc9, 0x36, 0xb8, 0x47, 0x56, 0x16, 0x02, 0x01, 0x00, 0x9e, 0xf0,
+/// ]));
+/// ```
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GetDirectoryCount(pub FileTransferVID, pub FileTransferOptions);
+
+impl Command for GetDirectoryCount {
+    type Response = i16;
+
+    fn encode_request(self) -> Result<(u8, Vec<u8>), crate::errors::DecodeError> {
+        
+        // The payload is just the vid and options
+        let payload = vec![self.0.to_u8(), self.1.bits()];
+
+        // Return the extended command with id 0x16
+        super::Extended(0x16, &payload).encode_request()
+    }
+
+    fn decode_response(command_id: u8, data: Vec<u8>) -> Result<Self::Response, crate::errors::DecodeError> {
+        
+        // Read the extended command
+        let payload = super::Extended::decode_response(command_id, data)?;
+
+        // Ensure that it is a response to 0x16
+        if payload.0 != 0x16 {
+            return Err(crate::errors::DecodeError::ExpectedCommand(0x16, payload.0));
+        }
+
+        // Ensure that the payload size is at least 2 bytes
+        if payload.1.len() < 2 {
+            return Err(crate::errors::DecodeError::PacketLengthError);
+        }
+
+        // The count is a little endian i16
+        Ok(i16::from_le_bytes(payload.1[0..2].try_into().unwrap()))
+    }
+}
+
+
+/// Gets the metadata of a file by its index in a directory
+/// 
+/// The directory is selected by the VID of the last [GetDirectoryCount], so that should be sent first,
+/// and the index should be less than the count it returned.
+/// 
+/// # Members
+/// 
+/// * `0` - The index of the file
+/// * `1` - The file transfer options, which are a single byte on the wire -- Use NONE
+/// 
+/// # Examples
+/// 
+/// ```rust
+/// use vexv5_serial::commands::{Command, GetFileMetadataByIndex};
+/// use vexv5_serial::v5::FileTransferOptions;
+/// 
+/// assert_eq!(GetFileMetadataByIndex(0, FileTransferOptions::NONE).encode_request().unwrap(), (0x56, vec![
+///     0xc9, 0x36, 0xb8, 0x47, 0x56, 0x17, 0x02, 0x00, 0x00, 0xdb, 0x75,
+/// ]));
+/// ```
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GetFileMetadataByIndex(pub u8, pub FileTransferOptions);
+
+impl Command for GetFileMetadataByIndex {
+    type Response = FileMetadataByIndex;
+
+    fn encode_request(self) -> Result<(u8, Vec<u8>), crate::errors::DecodeError> {
+        
+        // The payload is just the index and options
+        let payload = vec![self.0, self.1.bits()];
+
+        // Return the extended command with id 0x17
+        super::Extended(0x17, &payload).encode_request()
+    }
+
+    fn decode_response(command_id: u8, data: Vec<u8>) -> Result<Self::Response, crate::errors::DecodeError> {
+        
+        // Read the extended command
+        let payload = super::Extended::decode_response(command_id, data)?;
+
+        // Ensure that it is a response to 0x17
+        if payload.0 != 0x17 {
+            return Err(crate::errors::DecodeError::ExpectedCommand(0x17, payload.0));
+        }
+
+        // Ensure that the payload size is at least 49 bytes
+        if payload.1.len() < 49 {
+            return Err(crate::errors::DecodeError::PacketLengthError);
+        }
+
+        // Parse in the data
+        Ok(FileMetadataByIndex {
+            idx: payload.1[0],
+            length: u32::from_le_bytes(payload.1[1..5].try_into().unwrap()),
+            addr: u32::from_le_bytes(payload.1[5..9].try_into().unwrap()),
+            crc: u32::from_le_bytes(payload.1[9..13].try_into().unwrap()),
+            file_type: FileTransferType::from_bytes(payload.1[13..17].try_into().unwrap()),
+            timestamp: u32::from_le_bytes(payload.1[17..21].try_into().unwrap()),
+            version: u32::from_le_bytes(payload.1[21..25].try_into().unwrap()),
+            name: payload.1[25..49].try_into().unwrap(),
+        })
+    }
+}
+
+
+/// Erases a file from the brain
+/// 
+/// The erase is not complete until it is followed by a [FileTransferExit].
+/// 
+/// # Members
+/// 
+/// * `0` - The name of the file to erase
+/// * `1` - The VID of the file
+/// * `2` - The file transfer options, which are a single byte on the wire. Set [FileTransferOptions::ERASE_ALL] to also erase every file linked to this one.
+/// 
+/// # Examples
+/// 
+/// ```rust
+/// use vexv5_serial::commands::{Command, EraseFile, encode_file_name};
+/// use vexv5_serial::v5::{FileTransferVID, FileTransferOptions};
+/// 
+/// let erase = EraseFile(encode_file_name("slot_1.bin").unwrap(), FileTransferVID::User, FileTransferOptions::NONE);
+/// 
+/// // The vid and options come before the name
+/// assert_eq!(erase.encode_request().unwrap(), (0x56, vec![
+///     0xc9, 0x36, 0xb8, 0x47, 0x56, 0x1b, 0x1a, 0x01, 0x00, 0x73, 0x6c, 0x6f,
+///     0x74, 0x5f, 0x31, 0x2e, 0x62, 0x69, 0x6e, 0x00, 0x00, 0x00, 0x00, 0x00,
+///     0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x96, 0x5a,
+/// ]));
+/// ```
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EraseFile(pub [u8; 24], pub FileTransferVID, pub FileTransferOptions);
+
+impl Command for EraseFile {
+    type Response = ();
+
+    fn encode_request(self) -> Result<(u8, Vec<u8>), crate::errors::DecodeError> {
+        
+        // Create the payload with the vid and options
+        let mut payload = vec![self.1.to_u8(), self.2.bits()];
+
+        // Add the file name
+        payload.extend(self.0);
+
+        // Return the extended command with id 0x1B
+        super::Extended(0x1B, &payload).encode_request()
+    }
+
+    fn decode_response(command_id: u8, data: Vec<u8>) -> Result<Self::Response, crate::errors::DecodeError> {
+        
+        // Read the extended command
+        let payload = super::Extended::decode_response(command_id, data)?;
+
+        // Ensure that it is a response to 0x1B
+        if payload.0 != 0x1B {
+            return Err(crate::errors::DecodeError::ExpectedCommand(0x1B, payload.0));
+        }
+
+        Ok(())
+    }
+
+    fn modifies_device(&self) -> bool {
+        true
+    }
+}
+
+
+/// Runs a program file on the brain, or stops the running program
+/// 
+/// # Members
+/// 
+/// * `0` - The name of the file to run. This is ignored (and can be left empty) when stopping the program.
+/// * `1` - The VID of the file
+/// * `2` - The file transfer options, which are a single byte on the wire.
+///   Use NONE to run the file, or [ExecuteFile::STOP_PROGRAM] to stop the running program.
+/// 
+/// # Examples
+/// 
+/// ```rust
+/// use vexv5_serial::commands::{Command, ExecuteFile};
+/// use vexv5_serial::v5::FileTransferVID;
+/// 
+/// // Stop the running program
+/// assert_eq!(ExecuteFile([0; 24], FileTransferVID::User, ExecuteFile::STOP_PROGRAM).encode_request().unwrap(), (0x56, vec![
+///     0xc9, 0x36, 0xb8, 0x47, 0x56, 0x18, 0x1a, 0x01, 0x80, 0x00, 0x00, 0x00,
+///     0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+///     0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xa6, 0xc1,
+/// ]));
+/// ```
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExecuteFile(pub [u8; 24], pub FileTransferVID, pub FileTransferOptions);
+
+impl ExecuteFile {
+    /// Bit 8 is set when executing a file should stop the running program instead of running the file.
+    /// 
+    /// This matches the `run=False` path of PROS-CLI's `execute_program_file`. It is the same bit as
+    /// [FileTransferOptions::ERASE_ALL], so it is printed as `ERASE_ALL` when debugging the options.
+    pub const STOP_PROGRAM: FileTransferOptions = FileTransferOptions::from_bits_truncate(0b1000_0000);
+}
+
+impl Command for ExecuteFile {
+    type Response = ();
+
+    fn encode_request(self) -> Result<(u8, Vec<u8>), crate::errors::DecodeError> {
+        
+        // Create the payload with the vid and options
+        let mut payload = vec![self.1.to_u8(), self.2.bits()];
+
+        // Add the file name
+        payload.extend(self.0);
+
+        // Return the extended command with id 0x18
+        super::Extended(0x18, &payload).encode_request()
+    }
+
+    fn decode_response(command_id: u8, data: Vec<u8>) -> Result<Self::Response, crate::errors::DecodeError> {
+        
+        // Read the extended command
+        let payload = super::Extended::decode_response(command_id, data)?;
+
+        // Ensure that it is a response to 0x18
+        if payload.0 != 0x18 {
+            return Err(crate::errors::DecodeError::ExpectedCommand(0x18, payload.0));
+        }
+
+        Ok(())
+    }
+
+    fn modifies_device(&self) -> bool {
+        true
+    }
+}
@@ -19,9 +19,17 @@ use super::Command;
 /// 
 /// // The only other really useful key is the robotname key
 /// let kv = KVRead("robotname");
+/// 
+/// // The key is sent null-terminated
+/// use vexv5_serial::commands::Command;
+/// assert_eq!(KVRead("teamnumber").encode_request().unwrap(), (0x56, vec![
+///     0xc9, 0x36, 0xb8, 0x47, 0x56, 0x2e, 0x0b, 0x74, 0x65, 0x61, 0x6d, 0x6e,
+///     0x75, 0x6d, 0x62, 0x65, 0x72, 0x00, 0x9c, 0x7f,
+/// ]));
 ///
 /// ```
 #[derive(Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct KVRead<'a> (pub &'a str);
 
 impl<'a> Command for KVRead<'a> {
@@ -78,9 +86,18 @@ impl<'a> Command for KVRead<'a> {
 /// // We can also do the same with the robotname key, which is the
 /// // only other key that is useful to most users
 /// let kv = KVWrite("robotname", "robo");
+/// 
+/// // The key and the value are both sent null-terminated
+/// use vexv5_serial::commands::Command;
+/// assert_eq!(KVWrite("teamnumber", "ABCD").encode_request().unwrap(), (0x56, vec![
+///     0xc9, 0x36, 0xb8, 0x47, 0x56, 0x2f, 0x10, 0x74, 0x65, 0x61, 0x6d, 0x6e,
+///     0x75, 0x6d, 0x62, 0x65, 0x72, 0x00, 0x41, 0x42, 0x43, 0x44, 0x00, 0xde,
+///     0x04,
+/// ]));
 ///
 /// ```
 #[derive(Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct KVWrite<'a> (pub &'a str, pub &'a str);
 
 impl<'a>Command for KVWrite<'a> {
@@ -122,6 +139,7 @@ impl<'a>Command for KVWrite<'a> {
     }
 
     /// This returns `()`, and if a package is malformed or not recieved it may return an error.
+    /// If the brain refuses the write (for example, because the key is read-only), then this returns [crate::errors::DecodeError::NACK].
     fn decode_response(command_id: u8, data: Vec<u8>) -> Result<Self::Response, crate::errors::DecodeError> {
 
         // Decode as an extended packet
@@ -129,9 +147,13 @@ impl<'a>Command for KVWrite<'a> {
 
         // If the command id is wrong, then error
         if packet.0 != 0x2f {
-            return Err(crate::errors::DecodeError::ExpectedCommand(0x2e, packet.0));
+            return Err(crate::errors::DecodeError::ExpectedCommand(0x2f, packet.0));
         }
 
         Ok(())
     }
+
+    fn modifies_device(&self) -> bool {
+        true
+    }
 }
@@ -3,11 +3,14 @@
 mod kv;
 pub use kv::{KVRead, KVWrite};
 
+mod simple;
+pub use simple::{Simple, SimpleResponse};
+
 mod extended;
 pub use extended::{Extended, ExtendedResponse};
 
 mod system;
-pub use system::{GetSystemVersion, V5SystemVersion};
+pub use system::{GetSystemVersion, V5SystemVersion, GetSystemStatus, V5SystemStatus, GetSystemFlags, V5SystemFlags};
 
 mod file;
 pub use file::{
@@ -17,13 +20,29 @@ pub use file::{
     FileTransferSetLink,
     FileTransferWrite,
     FileTransferRead,
-    GetFileMetadataByName
+    GetFileMetadataByName,
+    GetDirectoryCount,
+    GetFileMetadataByIndex,
+    EraseFile,
+    ExecuteFile,
+    encode_file_name,
+    decode_file_name
 };
 
 mod remote;
 pub use remote::SwitchChannel;
 
+mod any;
+pub use any::{AnyCommand, AnyResponse};
+
 /// A command trait that every command implements
+/// 
+/// # Byte order
+/// 
+/// Multi-byte fields in command payloads (addresses, lengths, CRC32s, timestamps, and versions) are little endian.
+/// The framing of extended packets is big endian: the two byte length (when the payload is longer than 0x80 bytes)
+/// and the CRC16 at the end of the packet are both sent high byte first.
+/// Every command has an example that pins the exact bytes it encodes to.
 pub trait Command {
     type Response;
     /// Encodes the client (computer) -> host (vexos) request
@@ -39,4 +58,20 @@ pub trait Command {
     /// * `command_id` - The command ID of the recieved command
     /// * `data` - The vector of data that was sent in the command
     fn decode_response(command_id: u8, data: Vec<u8>) -> Result<Self::Response, crate::errors::DecodeError>;
+
+    /// Returns whether the device will send a response to this command.
+    /// 
+    /// Defaults to true. Commands that the device never responds to (such as one that reboots the device) should return false,
+    /// so that the device does not wait for a response that will never arrive. When this is false, the response is produced by calling
+    /// `decode_response` with the simple command id and an empty payload, so it must succeed on empty data.
+    fn expects_response(&self) -> bool {
+        true
+    }
+
+    /// Returns whether this command changes the state of the device, such as by writing, erasing, or running a file.
+    /// 
+    /// Defaults to false. Devices in read-only mode refuse to send commands that return true.
+    fn modifies_device(&self) -> bool {
+        false
+    }
 }
\ No newline at end of file
@@ -13,16 +13,23 @@ use super::Command;
 /// 
 /// ```rust
 /// 
-/// use vexv5_serial::commands::SwitchChannel;
+/// use vexv5_serial::commands::{Command, SwitchChannel};
+/// use vexv5_serial::v5::V5ControllerChannel;
 /// 
 /// // Create a SwitchChannel instance that will switch to the download channel
 /// let kv = SwitchChannel(V5ControllerChannel::Download);
 /// 
 /// // Create a SwitchChannel instance that will switch to the pit channel
 /// let kv = SwitchChannel(V5ControllerChannel::Pit);
+/// 
+/// // The channel is sent as a single byte
+/// assert_eq!(SwitchChannel(V5ControllerChannel::Download).encode_request().unwrap(), (0x56, vec![
+///     0xc9, 0x36, 0xb8, 0x47, 0x56, 0x10, 0x01, 0x01, 0x18, 0x73,
+/// ]));
 ///
 /// ```
 #[derive(Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SwitchChannel(pub V5ControllerChannel);
 
 impl Command for SwitchChannel {
@@ -2,7 +2,24 @@
 
 use super::Command;
 
+/// Gets the version and product type of the V5 device this command is sent to
+/// 
+/// This is a simple command, so when connected to a controller it is answered by the controller itself
+/// rather than by the brain it is paired with. See [GetSystemStatus] for querying the brain through a controller.
+/// 
+/// # Examples
+/// 
+/// ```rust
+/// use vexv5_serial::commands::{Command, GetSystemVersion};
+/// 
+/// // This is a simple command with no payload
+/// assert_eq!(GetSystemVersion().encode_request().unwrap(), (0xA4, vec![]));
+/// 
+/// // Responses too short to hold the version and product type are rejected
+/// assert!(GetSystemVersion::decode_response(0xA4, vec![1, 1, 2]).is_err());
+/// ```
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GetSystemVersion();
 
 impl Command for GetSystemVersion {
@@ -20,6 +37,11 @@ impl Command for GetSystemVersion {
             return Err(crate::errors::DecodeError::ExpectedExtended);
         }
 
+        // Ensure that the payload holds the five version bytes and the two product bytes
+        if data.len() < 7 {
+            return Err(crate::errors::DecodeError::PacketLengthError);
+        }
+
         // Alias to make code shorter
         let v = data;
 
@@ -32,7 +54,170 @@ impl Command for GetSystemVersion {
 }
 
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct V5SystemVersion {
     pub system_version: (u8, u8, u8, u8, u8),
     pub product_type: crate::v5::VexProductType
-}
\ No newline at end of file
+}
+
+impl V5SystemVersion {
+    /// Returns the system version decoded into a [crate::v5::SystemVersion]
+    pub fn version(&self) -> crate::v5::SystemVersion {
+        self.system_version.into()
+    }
+}
+
+/// Gets the status of the V5 system, including the firmware versions of each processor and the system's unique id
+/// 
+/// This is an extended command, so when connected to a controller that has been switched to the download channel
+/// it is forwarded to the paired brain.
+/// 
+/// # Examples
+/// 
+/// ```rust
+/// use vexv5_serial::commands::{Command, GetSystemStatus};
+/// 
+/// assert_eq!(GetSystemStatus().encode_request().unwrap(), (0x56, vec![
+///     0xc9, 0x36, 0xb8, 0x47, 0x56, 0x22, 0x00, 0x60, 0xfc,
+/// ]));
+/// ```
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GetSystemStatus();
+
+impl Command for GetSystemStatus {
+    type Response = V5SystemStatus;
+
+    fn encode_request(self) -> Result<(u8, Vec<u8>), crate::errors::DecodeError> {
+        // Encode an empty extended command with id 0x22
+        super::Extended(0x22, &[]).encode_request()
+    }
+
+    fn decode_response(command_id: u8, data: Vec<u8>) -> Result<Self::Response, crate::errors::DecodeError> {
+        // Decode the extended command
+        let payload = super::Extended::decode_response(command_id, data)?;
+
+        // Ensure that it is a response to 0x22
+        if payload.0 != 0x22 {
+            return Err(crate::errors::DecodeError::ExpectedCommand(0x22, payload.0));
+        }
+
+        // Ensure that the payload size is at least 21 bytes
+        // Newer firmware sends more bytes after this, but we do not know what they mean
+        if payload.1.len() < 21 {
+            return Err(crate::errors::DecodeError::PacketLengthError);
+        }
+
+        // Alias to make code shorter
+        // The first byte is unknown, and bytes 13..16 are padding
+        let v = payload.1;
+
+        Ok(V5SystemStatus {
+            system_version: (v[1], v[2], v[3], v[4]),
+            cpu0_version: (v[5], v[6], v[7], v[8]),
+            cpu1_version: (v[9], v[10], v[11], v[12]),
+            touch_version: v[16],
+            system_id: u32::from_le_bytes(v[17..21].try_into().unwrap()),
+        })
+    }
+}
+
+/// The status of the V5 system
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct V5SystemStatus {
+    /// The version of VexOS
+    pub system_version: (u8, u8, u8, u8),
+    /// The firmware version of CPU0
+    pub cpu0_version: (u8, u8, u8, u8),
+    /// The firmware version of CPU1
+    pub cpu1_version: (u8, u8, u8, u8),
+    /// The firmware version of the touch screen
+    pub touch_version: u8,
+    /// The unique id of this device
+    pub system_id: u32,
+}
+
+/// Gets the system flags of the V5, which include the battery levels and the currently running program
+/// 
+/// # Examples
+/// 
+/// ```rust
+/// use vexv5_serial::commands::{Command, GetSystemFlags};
+/// 
+/// assert_eq!(GetSystemFlags().encode_request().unwrap(), (0x56, vec![
+///     0xc9, 0x36, 0xb8, 0x47, 0x56, 0x20, 0x00, 0x06, 0x9e,
+/// ]));
+/// ```
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GetSystemFlags();
+
+impl Command for GetSystemFlags {
+    type Response = V5SystemFlags;
+
+    fn encode_request(self) -> Result<(u8, Vec<u8>), crate::errors::DecodeError> {
+        // Encode an empty extended command with id 0x20
+        super::Extended(0x20, &[]).encode_request()
+    }
+
+    fn decode_response(command_id: u8, data: Vec<u8>) -> Result<Self::Response, crate::errors::DecodeError> {
+        // Decode the extended command
+        let payload = super::Extended::decode_response(command_id, data)?;
+
+        // Ensure that it is a response to 0x20
+        if payload.0 != 0x20 {
+            return Err(crate::errors::DecodeError::ExpectedCommand(0x20, payload.0));
+        }
+
+        // Ensure that the payload size is at least 7 bytes
+        if payload.1.len() < 7 {
+            return Err(crate::errors::DecodeError::PacketLengthError);
+        }
+
+        Ok(V5SystemFlags {
+            flags: u32::from_le_bytes(payload.1[0..4].try_into().unwrap()),
+            byte_1: payload.1[4],
+            byte_2: payload.1[5],
+            current_program: payload.1[6],
+        })
+    }
+}
+
+/// The system flags of the V5
+/// 
+/// The meaning of these fields is not documented by Vex, and was taken from observations of other tools.
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct V5SystemFlags {
+    /// A bit mask of system flags, most of which are not understood
+    pub flags: u32,
+    /// The upper four bits are the brain's battery percent divided by 8,
+    /// and the lower four bits are the radio quality divided by 8
+    pub byte_1: u8,
+    /// The upper four bits are the controller's battery percent divided by 8,
+    /// and the lower four bits are the partner controller's battery percent divided by 8
+    pub byte_2: u8,
+    /// The slot of the program that is currently running, or zero if no program is running.
+    pub current_program: u8,
+}
+
+impl V5SystemFlags {
+    /// Returns the battery percent of the brain
+    pub fn battery_percent(&self) -> u8 {
+        (self.byte_1 >> 4) * 8
+    }
+
+    /// Returns the battery percent of the controller
+    pub fn controller_battery_percent(&self) -> u8 {
+        (self.byte_2 >> 4) * 8
+    }
+
+    /// Returns the slot of the program that is currently running, or None if no program is running.
+    pub fn running_program(&self) -> Option<u8> {
+        match self.current_program {
+            0 => None,
+            slot => Some(slot),
+        }
+    }
+}
@@ -18,6 +18,21 @@ const GATT_USER: Uuid = Uuid::from_u128(0x08590f7e_db05_467e_8757_72f6faeb1316);
 /// The system port GATT characteristic
 const GATT_SYSTEM: Uuid = Uuid::from_u128(0x08590f7e_db05_467e_8757_72f6faeb13e5);
 
+/// The magic number the brain sends when we connect
+const HANDSHAKE_MAGIC: u32 = 0xdeadface;
+
+/// The number of times to read from the brain when looking for the handshake magic number
+const HANDSHAKE_ATTEMPTS: usize = 3;
+
+/// The default number of times to try connecting to the brain
+pub const DEFAULT_CONNECT_ATTEMPTS: usize = 4;
+
+/// The default time to wait for a read from or write to the brain to complete
+pub const DEFAULT_IO_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The default delay before the first connection retry. This doubles after each failed attempt.
+pub const DEFAULT_CONNECT_BACKOFF: Duration = Duration::from_millis(250);
+
 
 
 
@@ -28,7 +43,11 @@ pub struct BluetoothBrain {
     system_char: Option<Characteristic>,
     user_char: Option<Characteristic>,
     service: Option<Service>,
-    device: AdvertisingDevice
+    device: AdvertisingDevice,
+    connect_attempts: usize,
+    connect_backoff: Duration,
+    io_timeout: Duration,
+    handshook: bool,
 }
 
 impl BluetoothBrain {
@@ -38,11 +57,46 @@ impl BluetoothBrain {
             system_char: None,
             user_char: None,
             service: None,
-            device
+            device,
+            connect_attempts: DEFAULT_CONNECT_ATTEMPTS,
+            connect_backoff: DEFAULT_CONNECT_BACKOFF,
+            io_timeout: DEFAULT_IO_TIMEOUT,
+            handshook: false,
         }
     }
 
-    /// Connects self to .ok_or(DeviceError::NotConnected)the brain
+    /// Returns the signal strength of the brain's advertisement in dBm, if the adapter reported one.
+    /// 
+    /// This is captured when scanning, so it does not change after the brain is discovered.
+    pub fn rssi(&self) -> Option<i16> {
+        self.device.rssi
+    }
+
+    /// Returns the local name the brain advertised, if it had one
+    pub fn local_name(&self) -> Option<&str> {
+        self.device.adv_data.local_name.as_deref()
+    }
+
+    /// Configures how [BluetoothBrain::connect] retries when connecting or discovering services fails.
+    /// 
+    /// `attempts` is the total number of tries (at least one is always made), and `backoff` is the delay before
+    /// the first retry, which doubles after each failed attempt.
+    /// Defaults to [DEFAULT_CONNECT_ATTEMPTS] and [DEFAULT_CONNECT_BACKOFF].
+    pub fn set_connect_retries(&mut self, attempts: usize, backoff: Duration) {
+        self.connect_attempts = attempts;
+        self.connect_backoff = backoff;
+    }
+
+    /// Sets how long to wait for a read from or write to the brain before failing with [DeviceError::Timeout].
+    /// Defaults to [DEFAULT_IO_TIMEOUT].
+    pub fn set_io_timeout(&mut self, timeout: Duration) {
+        self.io_timeout = timeout;
+    }
+
+    /// Connects self to the brain
+    /// 
+    /// Connecting and discovering services often fails on the first try, so both are retried with exponential backoff
+    /// (see [BluetoothBrain::set_connect_retries]). If every attempt fails, [DeviceError::InvalidDevice] is returned.
     pub async fn connect(&mut self) -> Result<(), DeviceError> {
 
         // Create the adapter
@@ -52,28 +106,27 @@ impl BluetoothBrain {
         //    )?
         //);
 
+        // A new connection has to be handshaken with again
+        self.handshook = false;
+
         // Wait for the adapter to be available
         self.adapter.wait_available().await?;
 
-        // For some reason we need a little delay in here
-        tokio::time::sleep(Duration::from_millis(100)).await;
-
-        // Connect to the device
-        self.adapter.connect_device(&self.device.device).await?;
+        // Try to connect, backing off exponentially between attempts
+        let mut backoff = self.connect_backoff;
+        let mut attempt = 1;
         
-        // And here too
-        tokio::time::sleep(Duration::from_millis(100)).await;
-
-        // Get all services on the brain
-        let services = self.device.device.discover_services().await?;
-
-        // Find the vex service
-        self.service = Some(
-            services.iter().find(|v| {
-                v.uuid() == GATT_SYSTEM
-            }).ok_or(DeviceError::InvalidDevice)?.clone()
-        ); 
-        println!("ok");
+        self.service = loop {
+            match self.try_connect().await {
+                Ok(service) => break Some(service),
+                Err(_) if attempt < self.connect_attempts => {
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                    attempt += 1;
+                },
+                Err(_) => return Err(DeviceError::InvalidDevice),
+            }
+        };
         if let Some(service) = &self.service {
             
             // Get all characteristics of this service
@@ -102,44 +155,82 @@ impl BluetoothBrain {
         Ok(())
     }
 
-    /// Handshakes with the device, telling it we have connected
-    pub async fn handshake(&self) -> Result<(), DeviceError> {
+    /// Makes a single attempt to connect to the brain and find the vex service
+    async fn try_connect(&self) -> Result<Service, DeviceError> {
+        // For some reason we need a little delay in here
+        tokio::time::sleep(Duration::from_millis(100)).await;
 
-        // Read data from the system characteristic,
-        // making sure that it equals 0xdeadface (big endian)
-        let data = self.read_system().await?;
+        // Connect to the device
+        self.adapter.connect_device(&self.device.device).await?;
+        
+        // And here too
+        tokio::time::sleep(Duration::from_millis(100)).await;
 
-        // If there are not four bytes, then error
-        if data.len() != 4 {
-            return Err(DeviceError::InvalidMagic);
-        }
+        // Get all services on the brain
+        let services = self.device.device.discover_services().await?;
 
-        // Parse the bytes into a big endian u32
-        let magic = u32::from_be_bytes(data.try_into().unwrap());
+        // Find the vex service
+        Ok(services.iter().find(|v| {
+            v.uuid() == GATT_SYSTEM
+        }).ok_or(DeviceError::InvalidDevice)?.clone())
+    }
 
-        // If the magic number is nod 0xdeadface, then it is an invalid device
-        if magic != 0xdeadface {
-            return Err(DeviceError::InvalidMagic);
+    /// Handshakes with the device, telling it we have connected
+    /// 
+    /// The first read after connecting sometimes contains stale bytes, so the magic number
+    /// is searched for anywhere in the data, and the read is retried a few times before failing.
+    /// The brain ignores anything sent before the handshake, so reads and writes fail with
+    /// [DeviceError::NotAuthenticated] until it has succeeded.
+    pub async fn handshake(&mut self) -> Result<(), DeviceError> {
+
+        for _ in 0..HANDSHAKE_ATTEMPTS {
+            // Read data from the system characteristic,
+            // making sure that it contains 0xdeadface (big endian)
+            let data = self.read_system_unchecked().await?;
+
+            // If any four bytes are the magic number, then the handshake succeeded
+            if data.windows(4).any(|w| w == HANDSHAKE_MAGIC.to_be_bytes()) {
+                self.handshook = true;
+                return Ok(());
+            }
         }
 
-        println!("{magic:x}");
-
-        Ok(())
+        // If the magic number was never found, then it is an invalid device
+        Err(DeviceError::InvalidMagic)
     }
 
     /// Writes to the system port
+    /// 
+    /// Fails with [DeviceError::Timeout] if the write does not complete within the io timeout (see [BluetoothBrain::set_io_timeout]),
+    /// and with [DeviceError::NotAuthenticated] if [BluetoothBrain::handshake] has not succeeded.
     pub async fn write_system(&self, buf: &[u8]) -> Result<(), DeviceError> {
+        if self.system_char.is_some() && !self.handshook {
+            return Err(DeviceError::NotAuthenticated);
+        }
+
         if let Some(system) = &self.system_char {
-            Ok(system.write(buf).await?)
+            Ok(tokio::time::timeout(self.io_timeout, system.write(buf)).await.map_err(|_| DeviceError::Timeout)??)
         } else {
             Err(DeviceError::NotConnected)
         }
     }
 
     /// Reads from the system port
+    /// 
+    /// Fails with [DeviceError::Timeout] if the read does not complete within the io timeout (see [BluetoothBrain::set_io_timeout]),
+    /// and with [DeviceError::NotAuthenticated] if [BluetoothBrain::handshake] has not succeeded.
     pub async fn read_system(&self) -> Result<Vec<u8>, DeviceError> {
+        if self.system_char.is_some() && !self.handshook {
+            return Err(DeviceError::NotAuthenticated);
+        }
+
+        self.read_system_unchecked().await
+    }
+
+    /// Reads from the system port, whether or not the handshake has succeeded
+    async fn read_system_unchecked(&self) -> Result<Vec<u8>, DeviceError> {
         if let Some(system) = &self.system_char {
-            Ok(system.read().await?)
+            Ok(tokio::time::timeout(self.io_timeout, system.read()).await.map_err(|_| DeviceError::Timeout)??)
         } else {
             Err(DeviceError::NotConnected)
         }
@@ -159,17 +250,50 @@ impl BluetoothBrain {
 
 
 
-/// Discovers all V5 devices that are advertising over bluetooth.
+/// Lists the bluetooth adapters that can be used to scan for brains.
+/// 
+/// bluest only exposes the system's default adapter, so this returns at most one adapter, and other radios
+/// (such as a USB dongle next to an internal adapter) can not be listed or selected. To scan with a different radio,
+/// it has to be made the system's default adapter.
+pub async fn list_adapters() -> Vec<Adapter> {
+    Adapter::default().await.into_iter().collect()
+}
+
+/// Discovers all V5 devices that are advertising over bluetooth, using the default adapter.
 /// By default it scans for 5 seconds, but this can be configured
 pub async fn scan_for_v5_devices(timeout: Option<Duration>) -> Result<Vec<BluetoothBrain>, DeviceError> {
 
-    // If timeout is None, then default to five seconds
-    let timeout = timeout.unwrap_or_else(|| Duration::new(5, 0));
-
-    // Get the adapter and wait for it to be available
+    // Get the default adapter
     let adapter = Adapter::default().await.ok_or(DeviceError::NoBluetoothAdapter)?;
+
+    scan_for_v5_devices_on(adapter, timeout).await
+}
+
+/// Discovers all V5 devices that are advertising over bluetooth, using the given adapter.
+/// By default it scans for 5 seconds, but this can be configured
+pub async fn scan_for_v5_devices_on(adapter: Adapter, timeout: Option<Duration>) -> Result<Vec<BluetoothBrain>, DeviceError> {
+    scan_for_v5_devices_until(adapter, timeout, std::future::pending::<()>()).await
+}
+
+/// Discovers all V5 devices that are advertising over bluetooth using the given adapter, stopping early if `cancel` completes.
+/// 
+/// `cancel` can be any future, such as a [tokio::sync::oneshot::Receiver] (which also completes if its sender is dropped)
+/// or a cancellation token's `cancelled()` future. Either way, the devices discovered before the scan stopped are returned.
+/// By default it scans for 5 seconds, but this can be configured
+pub async fn scan_for_v5_devices_until(adapter: Adapter, timeout: Option<Duration>, cancel: impl std::future::Future) -> Result<Vec<BluetoothBrain>, DeviceError> {
+
+    // Wait for the adapter to be available
     adapter.wait_available().await?;
 
+    scan_available(&adapter, timeout, cancel).await
+}
+
+/// Scans for V5 devices on an adapter that is already available
+async fn scan_available(adapter: &Adapter, timeout: Option<Duration>, cancel: impl std::future::Future) -> Result<Vec<BluetoothBrain>, DeviceError> {
+
+    // If timeout is None, then default to five seconds
+    let timeout = timeout.unwrap_or_else(|| Duration::new(5, 0));
+
     // Create the GATT UUID
     let service: bluest::Uuid = GATT_SERVICE.try_into().unwrap();
     let service = &[service];
@@ -180,15 +304,23 @@ pub async fn scan_for_v5_devices(timeout: Option<Duration>) -> Result<Vec<Blueto
     // Set a timeout
     let timeout_stream = scan_stream.timeout(timeout);
     tokio::pin!(timeout_stream);
+    tokio::pin!(cancel);
 
     // Find the current time
     let time = std::time::SystemTime::now();
 
     let mut devices = Vec::<BluetoothBrain>::new();
 
-    // Find each device
-    while let Ok(Some(discovered_device)) = timeout_stream.try_next().await {
-        devices.push(BluetoothBrain::new(adapter.clone(), discovered_device));
+    // Find each device, until the scan times out or is cancelled
+    loop {
+        tokio::select! {
+            _ = &mut cancel => break,
+            discovered = timeout_stream.try_next() => match discovered {
+                Ok(Some(discovered_device)) => devices.push(BluetoothBrain::new(adapter.clone(), discovered_device)),
+                _ => break,
+            },
+        }
+
         // If over timeout has passed, then break
         if time.elapsed().unwrap() >= timeout {
             break;
@@ -197,4 +329,53 @@ pub async fn scan_for_v5_devices(timeout: Option<Duration>) -> Result<Vec<Blueto
 
     // These are our brains
     Ok(devices)
-}
\ No newline at end of file
+}
+
+
+/// Scans for V5 devices repeatedly, reusing the same adapter for every scan.
+/// 
+/// The free scan functions get the default adapter and wait for it to become available on every call.
+/// A scanner does this once when it is created, so tools that scan often (such as a device picker that
+/// refreshes every few seconds) only pay for the scan itself. The time saved has not been measured, and depends on how long
+/// the platform's bluetooth stack takes to return the default adapter and report it as available.
+/// 
+/// The adapter is only checked when the scanner is created. If it becomes unavailable afterwards
+/// (for example, if bluetooth is turned off), then scans return the adapter's error.
+#[derive(Clone)]
+pub struct BluetoothScanner {
+    adapter: Adapter,
+}
+
+impl BluetoothScanner {
+    /// Creates a scanner that uses the default adapter, waiting for it to become available
+    pub async fn new() -> Result<Self, DeviceError> {
+        let adapter = Adapter::default().await.ok_or(DeviceError::NoBluetoothAdapter)?;
+
+        Self::with_adapter(adapter).await
+    }
+
+    /// Creates a scanner that uses the given adapter, waiting for it to become available
+    pub async fn with_adapter(adapter: Adapter) -> Result<Self, DeviceError> {
+        adapter.wait_available().await?;
+
+        Ok(Self { adapter })
+    }
+
+    /// Returns the adapter this scanner uses
+    pub fn adapter(&self) -> &Adapter {
+        &self.adapter
+    }
+
+    /// Discovers all V5 devices that are advertising over bluetooth.
+    /// By default it scans for 5 seconds, but this can be configured
+    pub async fn scan(&self, timeout: Option<Duration>) -> Result<Vec<BluetoothBrain>, DeviceError> {
+        scan_available(&self.adapter, timeout, std::future::pending::<()>()).await
+    }
+
+    /// Discovers all V5 devices that are advertising over bluetooth, stopping early if `cancel` completes.
+    /// 
+    /// This works the same way as [scan_for_v5_devices_until].
+    pub async fn scan_until(&self, timeout: Option<Duration>, cancel: impl std::future::Future) -> Result<Vec<BluetoothBrain>, DeviceError> {
+        scan_available(&self.adapter, timeout, cancel).await
+    }
+}
@@ -2,13 +2,31 @@
 
 use std::io::{Read, Write};
 
+/// How often to poll the brain when waiting for a program to exit
+const PROGRAM_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+
+/// How long to wait for each response when waiting for the device to be ready
+const READY_ATTEMPT_TIMEOUT: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// How long to wait for the rest of a packet once its header has been recieved
+const PACKET_BODY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(1);
+
 
 /// The representation of a V5 device
+/// 
+/// This is [Send] as long as both ports are [Send], so it can be moved to another thread.
 pub struct Device<S: Read + Write, U: Read+Write> {
     system_port: S,
     user_port: Option<U>,
     read_buffer: Vec<u8>,
     user_read_size: u8,
+    command_delay: std::time::Duration,
+    ack_observer: Option<super::AckObserver>,
+    packet_tracer: Option<super::PacketTracer>,
+    system_version: Option<crate::commands::V5SystemVersion>,
+    stats: Option<super::TransportStats>,
+    device_type: Option<super::VexDeviceType>,
+    read_only: bool,
 }
 
 impl<S: Read + Write, U: Read+Write> Device<S, U> {
@@ -19,38 +37,382 @@ impl<S: Read + Write, U: Read+Write> Device<S, U> {
             user_port,
             read_buffer: Vec::new(),
             user_read_size: 0x20, // By default, read chunks of 32 bytes
+            command_delay: std::time::Duration::ZERO,
+            ack_observer: None,
+            packet_tracer: None,
+            system_version: None,
+            stats: None,
+            device_type: None,
+            read_only: false,
+        }
+    }
+
+    /// Returns the system version of the device.
+    /// 
+    /// The version is requested the first time this is called, and cached for the rest of the connection.
+    pub fn system_version(&mut self) -> Result<crate::commands::V5SystemVersion, crate::errors::DecodeError> {
+        if let Some(version) = self.system_version {
+            return Ok(version);
         }
+
+        let version = self.send_request(crate::system::GetSystemVersion())?;
+        self.system_version = Some(version);
+
+        Ok(version)
     }
 
     /// Returns true if this device is a controller
     pub fn is_controller(&mut self) -> Result<bool, crate::errors::DecodeError> {
         // Get the vex system info
         // Return true if this is a controller
-        Ok(match self.send_request(crate::system::GetSystemVersion())?.product_type {
+        Ok(match self.system_version()?.product_type {
             crate::system::VexProductType::V5Brain(_) => false,
             crate::system::VexProductType::V5Controller(_) => true,
         })
     }
 
+    /// Returns true if this device is a controller connected to a brain over VEXLink.
+    /// 
+    /// See [crate::v5::VexProductType::is_wireless_controller].
+    pub fn is_wireless_controller(&mut self) -> Result<bool, crate::errors::DecodeError> {
+        Ok(self.system_version()?.product_type.is_wireless_controller())
+    }
+
+    /// Returns the unique id of this device as a hex string.
+    /// 
+    /// This is the system id reported by [crate::commands::GetSystemStatus], which stays the same across sessions.
+    pub fn get_brain_id(&mut self) -> Result<String, crate::errors::DecodeError> {
+        let status = self.send_request(crate::commands::GetSystemStatus())?;
+
+        Ok(format!("{:08X}", status.system_id))
+    }
+
+    /// Returns the status of the brain, even when connected through a controller.
+    /// 
+    /// Controllers answer [crate::commands::GetSystemVersion] themselves, but forward extended commands to the paired brain
+    /// once they are on the download channel. If this device is a controller, it is switched to the download channel
+    /// (and left on it) before the status is requested.
+    pub fn brain_system_status(&mut self) -> Result<crate::commands::V5SystemStatus, crate::errors::DecodeError> {
+        // Controllers need to be on the download channel to reach the brain
+        if self.is_controller()? {
+            self.send_request(crate::commands::SwitchChannel(crate::v5::V5ControllerChannel::Download))?;
+        }
+
+        self.send_request(crate::commands::GetSystemStatus())
+    }
+
+    /// Returns the version of VEXos installed on the brain, formatted as `major.minor.patch-buildNN`.
+    /// 
+    /// No key that holds a firmware version string is known, so this is built from the version reported by
+    /// [Self::brain_system_status], which also works through a controller.
+    pub fn vexos_version(&mut self) -> Result<String, crate::errors::DecodeError> {
+        let (major, minor, patch, build) = self.brain_system_status()?.system_version;
+
+        Ok(crate::v5::SystemVersion::from((major, minor, patch, build, 0)).to_string())
+    }
+
+    /// Returns the slot of the program that is currently running, or None if no program is running.
+    pub fn running_program(&mut self) -> Result<Option<u8>, crate::errors::DecodeError> {
+        Ok(self.send_request(crate::commands::GetSystemFlags())?.running_program())
+    }
+
+    /// Stops the program that is running on the brain.
+    /// 
+    /// Returns [crate::errors::DecodeError::InvalidValue] if no program is running.
+    pub fn stop_program(&mut self) -> Result<(), crate::errors::DecodeError> {
+        if self.running_program()?.is_none() {
+            return Err(crate::errors::DecodeError::InvalidValue("no program is running".to_string()));
+        }
+
+        // Executing with the stop option stops the running program, whatever the name is
+        self.send_request(crate::commands::ExecuteFile([0; 24], crate::v5::FileTransferVID::User, crate::commands::ExecuteFile::STOP_PROGRAM))
+    }
+
+    /// Waits until no program is running on the brain, polling [Device::running_program].
+    /// 
+    /// Returns [crate::errors::DecodeError::HeaderTimeout] if a program is still running after `timeout`.
+    pub fn wait_for_program_exit(&mut self, timeout: std::time::Duration) -> Result<(), crate::errors::DecodeError> {
+        let deadline = std::time::SystemTime::now() + timeout;
+
+        while self.running_program()?.is_some() {
+            // Give up once the timeout has passed
+            if std::time::SystemTime::now() >= deadline {
+                return Err(crate::errors::DecodeError::HeaderTimeout);
+            }
+
+            std::thread::sleep(PROGRAM_POLL_INTERVAL);
+        }
+
+        Ok(())
+    }
+
+    /// Waits until the device responds to [crate::commands::GetSystemVersion], such as while the brain is rebooting.
+    /// 
+    /// Timeouts and IO errors are ignored while waiting, and any other error is returned. If the reboot closed the port itself
+    /// (as it usually does for USB serial ports), the device has to be found and opened again instead.
+    /// Returns [crate::errors::DecodeError::HeaderTimeout] if the device has not responded after `timeout`.
+    pub fn wait_until_ready(&mut self, timeout: std::time::Duration) -> Result<(), crate::errors::DecodeError> {
+        let deadline = std::time::SystemTime::now() + timeout;
+
+        loop {
+            // Give up once the timeout has passed
+            let remaining = deadline.duration_since(std::time::SystemTime::now()).unwrap_or_default();
+            if remaining.is_zero() {
+                return Err(crate::errors::DecodeError::HeaderTimeout);
+            }
+
+            // Only wait a short time for each response, so that a request sent before the device was ready does not use up the timeout
+            let attempt = match self.send_command(crate::commands::GetSystemVersion()) {
+                Ok(()) => self.response_to(crate::commands::GetSystemVersion(), remaining.min(READY_ATTEMPT_TIMEOUT)),
+                Err(e) => Err(e),
+            };
+
+            match attempt {
+                Ok(version) => {
+                    self.system_version = Some(version);
+                    return Ok(());
+                }
+                Err(crate::errors::DecodeError::HeaderTimeout) | Err(crate::errors::DecodeError::IoError(_)) => std::thread::sleep(PROGRAM_POLL_INTERVAL),
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Returns a mutable reference to the underlying system port, for transport-specific configuration
+    /// such as changing timeouts or toggling RTS/DTR.
+    /// 
+    /// Reading from or writing to this port directly can desync the packet parser, so the port should only
+    /// be used for configuration between commands.
+    pub fn system_port_mut(&mut self) -> &mut S {
+        &mut self.system_port
+    }
+
+    /// Sets the type of this device, which is done automatically when it is opened from a [super::VexDevice].
+    /// 
+    /// Devices that were found without a known type (see [super::VexDeviceType::Unknown]) can not read the program's
+    /// output over the system port, since there may be no program on the other end. If the type is known some other way,
+    /// such as from [Self::system_version], setting it here allows those reads again.
+    pub fn set_device_type(&mut self, device_type: super::VexDeviceType) {
+        self.device_type = Some(device_type);
+    }
+
+    /// Returns the type of this device, if it is known.
+    /// 
+    /// This is None for devices that were created directly from their ports rather than opened from a [super::VexDevice].
+    pub fn device_type(&self) -> Option<&super::VexDeviceType> {
+        self.device_type.as_ref()
+    }
+
+    /// Returns the size and layout of the device's display, for reading screen captures.
+    /// 
+    /// Only brains have a display that can be captured, so this returns [crate::errors::DeviceError::InvalidDevice] for controllers,
+    /// and [crate::errors::DeviceError::UnknownDeviceType] if the type of the device is not known.
+    pub fn display_info(&self) -> Result<crate::v5::DisplayInfo, crate::errors::DecodeError> {
+        match self.device_type {
+            Some(super::VexDeviceType::Brain) => Ok(crate::v5::DisplayInfo::V5_BRAIN),
+            Some(super::VexDeviceType::Controller) => Err(crate::errors::DeviceError::InvalidDevice.into()),
+            Some(super::VexDeviceType::Unknown) | None => Err(crate::errors::DeviceError::UnknownDeviceType.into()),
+        }
+    }
+
+    /// Sets whether the device is in read-only mode.
+    /// 
+    /// In read-only mode, any command that would modify the device (see [crate::commands::Command::modifies_device]),
+    /// such as a key-value write, an upload, an erase, or running a file, returns [crate::errors::DeviceError::ReadOnly]
+    /// without being sent. Commands that only read from the device work as usual. This does not block writing to the
+    /// user program's serial output, which does not change anything stored on the device.
+    /// 
+    /// # Examples
+    /// 
+    /// ```rust
+    /// use vexv5_serial::commands::KVWrite;
+    /// use vexv5_serial::devices::device::Device;
+    /// use vexv5_serial::devices::replay::FixedStream;
+    /// use vexv5_serial::errors::{DecodeError, DeviceError};
+    /// 
+    /// let mut device = Device::<_, FixedStream>::new(FixedStream::new(Vec::new()), None);
+    /// device.set_read_only(true);
+    /// 
+    /// let result = device.send_request(KVWrite("teamnumber", "ABCD"));
+    /// assert!(matches!(result, Err(DecodeError::DeviceError(DeviceError::ReadOnly))));
+    /// 
+    /// // Nothing was sent to the device
+    /// assert!(device.system_port_mut().written().is_empty());
+    /// ```
+    pub fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
+    }
+
+    /// Returns true if the device is in read-only mode
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Returns true if this device has a user port.
+    /// 
+    /// Without one, reading from the device falls back to reading the program's output over the system port.
+    /// Having a user port does not mean that a program is running to write to it; see [Self::running_program] for that.
+    pub fn user_port_available(&self) -> bool {
+        self.user_port.is_some()
+    }
+
+    /// Returns a mutable reference to the underlying user port (if it was opened), for transport-specific
+    /// configuration such as changing timeouts or toggling RTS/DTR.
+    pub fn user_port_mut(&mut self) -> Option<&mut U> {
+        self.user_port.as_mut()
+    }
+
+    /// Splits the user port off from this device, so that it can be used at the same time as the system port.
+    /// 
+    /// Returns this device without its user port (the system half), along with the user port (the user half),
+    /// or None if there was no user port. Each half can be moved to its own thread or task (for example with `std::thread::spawn`),
+    /// so the user program can be written to while system commands are being sent. Use [Device::unsplit] to put the user port back.
+    /// 
+    /// The two ports are separate streams, so this is safe: the system half only uses the system port, and the user half only
+    /// uses the user port. Bytes written to the user half reach the user program in order, but there is no ordering between them
+    /// and the commands sent on the system half. While split, reading from the system half falls back to reading the program's
+    /// output over the system port (see [Device::read_serial]), which takes output that the user half would otherwise recieve,
+    /// and writing to the system half fails with [crate::errors::DeviceError::NoWriteOnWireless].
+    pub fn split(mut self) -> (Self, Option<U>) {
+        let user_port = self.user_port.take();
+
+        (self, user_port)
+    }
+
+    /// Puts a user port that was split off with [Device::split] back into this device, replacing any user port it has
+    pub fn unsplit(&mut self, user_port: U) {
+        self.user_port = Some(user_port);
+    }
+
     /// Updates the size of the chunks to read from the system port when a user port is not available
     pub fn update_user_read_size(&mut self, user_read_size: u8) {
         self.user_read_size = user_read_size;
     }
 
+    /// Sets a delay to wait before sending each command. Defaults to zero.
+    /// 
+    /// Some brains (especially over bluetooth) drop or NACK commands that arrive too quickly after a response,
+    /// and a small delay between commands works around this.
+    pub fn set_command_delay(&mut self, delay: std::time::Duration) {
+        self.command_delay = delay;
+    }
+
+    /// Enables or disables collecting [super::TransportStats]. Stats are disabled by default to avoid the overhead.
+    /// 
+    /// Enabling stats (even if they were already enabled) resets them to zero.
+    pub fn set_stats_enabled(&mut self, enabled: bool) {
+        self.stats = if enabled { Some(super::TransportStats::default()) } else { None };
+    }
+
+    /// Returns the stats collected since they were enabled, or None if they are disabled
+    pub fn stats(&self) -> Option<super::TransportStats> {
+        self.stats
+    }
+
+    /// Sets a hook that is called with the raw ACK byte and its decoded type for every extended response recieved,
+    /// whether it is an ACK or a NACK. ACK bytes that are not a known [crate::errors::VexACKType] are not passed to the hook.
+    /// 
+    /// This is intended for diagnostics, such as logging the distribution of ACK codes over a session.
+    pub fn set_ack_observer(&mut self, observer: impl Fn(u8, crate::errors::VexACKType) + Send + 'static) {
+        self.ack_observer = Some(Box::new(observer));
+    }
+
+    /// Sets a hook that is called with every full packet sent or recieved on the system port, from the first byte of its header
+    /// to the last byte of its CRC (if it has one).
+    /// 
+    /// This is intended for debugging the protocol, such as hex-dumping the traffic when a command fails.
+    /// Packets are passed to the hook before they are decoded, so it also sees packets that fail to decode.
+    pub fn set_packet_tracer(&mut self, tracer: impl Fn(super::PacketDirection, &[u8]) + Send + 'static) {
+        self.packet_tracer = Some(Box::new(tracer));
+    }
+
     /// Sends a command and recieves its response
     pub fn send_request<C: crate::commands::Command + Copy>(&mut self, command: C) -> Result<C::Response, crate::errors::DecodeError> {
         // Send the command over the system port
+        let start = std::time::Instant::now();
         self.send_command(command)?;
-        
-        // Wait for the response
-        self.response_for::<C>(std::time::Duration::new(crate::devices::SERIAL_TIMEOUT_SECONDS, crate::devices::SERIAL_TIMEOUT_NS))
+
+        let result = if command.expects_response() {
+            // Wait for the response
+            self.response_to(command, std::time::Duration::new(crate::devices::SERIAL_TIMEOUT_SECONDS, crate::devices::SERIAL_TIMEOUT_NS))
+        } else {
+            // If the device will not respond, then decode an empty response instead of waiting
+            C::decode_response(command.encode_request()?.0, Vec::new())
+        };
+
+        if let Some(stats) = &mut self.stats {
+            stats.total_time += start.elapsed();
+        }
+
+        result
+    }
+
+    /// Sends a command that was chosen at runtime and recieves its response.
+    /// 
+    /// This sends the wrapped command with `send_request`, and wraps its response in the matching [crate::commands::AnyResponse] variant.
+    pub fn send_any(&mut self, command: crate::commands::AnyCommand<'_>) -> Result<crate::commands::AnyResponse, crate::errors::DecodeError> {
+        use crate::commands::{AnyCommand, AnyResponse};
+
+        Ok(match command {
+            AnyCommand::Simple(c) => AnyResponse::Simple(self.send_request(c)?),
+            AnyCommand::Extended(c) => AnyResponse::Extended(self.send_request(c)?),
+            AnyCommand::GetSystemVersion(c) => AnyResponse::GetSystemVersion(self.send_request(c)?),
+            AnyCommand::GetSystemStatus(c) => AnyResponse::GetSystemStatus(self.send_request(c)?),
+            AnyCommand::GetSystemFlags(c) => AnyResponse::GetSystemFlags(self.send_request(c)?),
+            AnyCommand::SwitchChannel(c) => {
+                self.send_request(c)?;
+                AnyResponse::SwitchChannel
+            }
+            AnyCommand::KVRead(c) => AnyResponse::KVRead(self.send_request(c)?),
+            AnyCommand::KVWrite(c) => {
+                self.send_request(c)?;
+                AnyResponse::KVWrite
+            }
+            AnyCommand::FileTransferInit(c) => AnyResponse::FileTransferInit(self.send_request(c)?),
+            AnyCommand::FileTransferExit(c) => {
+                self.send_request(c)?;
+                AnyResponse::FileTransferExit
+            }
+            AnyCommand::FileTransferSetLink(c) => {
+                self.send_request(c)?;
+                AnyResponse::FileTransferSetLink
+            }
+            AnyCommand::FileTransferWrite(c) => {
+                self.send_request(c)?;
+                AnyResponse::FileTransferWrite
+            }
+            AnyCommand::FileTransferRead(c) => AnyResponse::FileTransferRead(self.send_request(c)?),
+            AnyCommand::GetFileMetadataByName(c) => AnyResponse::GetFileMetadataByName(self.send_request(c)?),
+            AnyCommand::GetDirectoryCount(c) => AnyResponse::GetDirectoryCount(self.send_request(c)?),
+            AnyCommand::GetFileMetadataByIndex(c) => AnyResponse::GetFileMetadataByIndex(self.send_request(c)?),
+            AnyCommand::EraseFile(c) => {
+                self.send_request(c)?;
+                AnyResponse::EraseFile
+            }
+            AnyCommand::ExecuteFile(c) => {
+                self.send_request(c)?;
+                AnyResponse::ExecuteFile
+            }
+        })
     }
 
     /// Sends a command
     pub fn send_command<C: crate::commands::Command + Copy>(&mut self, command: C) -> Result<(), crate::errors::DecodeError> {
+        self.send_encoded(command.encode_request()?, command.modifies_device())
+    }
+
+    /// Sends a command that has already been encoded
+    fn send_encoded(&mut self, encoded: (u8, Vec<u8>), modifies_device: bool) -> Result<(), crate::errors::DecodeError> {
 
-        // Encode the command
-        let encoded = command.encode_request()?;
+        // Refuse commands that would modify the device in read-only mode
+        if self.read_only && modifies_device {
+            return Err(crate::errors::DeviceError::ReadOnly.into());
+        }
+
+        // Wait for the command delay, if there is one
+        if !self.command_delay.is_zero() {
+            std::thread::sleep(self.command_delay);
+        }
 
         // Create the packet
         let packet = if encoded.0 == 0x56 {
@@ -63,6 +425,15 @@ impl<S: Read + Write, U: Read+Write> Device<S, U> {
             data
         };
         
+        if let Some(stats) = &mut self.stats {
+            stats.commands_sent += 1;
+            stats.bytes_tx += packet.len() as u64;
+        }
+
+        if let Some(tracer) = &self.packet_tracer {
+            tracer(super::PacketDirection::Sent, &packet);
+        }
+
         // Write the command to the serial port
         match self.system_port.write_all(&packet) {
             Ok(_) => (),
@@ -77,15 +448,181 @@ impl<S: Read + Write, U: Read+Write> Device<S, U> {
         Ok(())
     }
 
+    /// Sends a batch of commands before reading any of their responses, then matches each response to its command.
+    /// 
+    /// The commands can be of different types, so they are wrapped in [crate::commands::AnyCommand].
+    /// Each response is matched by its command id to the oldest command with that id that has not yet been answered,
+    /// and is decoded by that command's decoder, so responses to different commands may arrive in any order.
+    /// Responses that do not match any outstanding command, and packets that are malformed, are discarded.
+    /// If a command is not answered within `timeout` of being sent, its result is [crate::errors::DecodeError::HeaderTimeout],
+    /// while the other commands keep waiting. A response whose header arrives in time is still read in full, which can take
+    /// up to a second past the timeout, rather than being cut off and lost. If the port fails, every command that has not
+    /// been answered fails with the port's error.
+    /// 
+    /// The results are returned in the same order as `commands`.
+    pub fn pipeline(&mut self, commands: &[crate::commands::AnyCommand<'_>], timeout: std::time::Duration) -> Vec<Result<crate::commands::AnyResponse, crate::errors::DecodeError>> {
+        // The result for each command, filled in as responses arrive
+        let mut results: Vec<Option<Result<crate::commands::AnyResponse, crate::errors::DecodeError>>> = commands.iter().map(|_| None).collect();
+
+        // The commands that are still waiting on a response, as (index, command id, deadline)
+        let mut pending = Vec::<(usize, (u8, Option<u8>), std::time::SystemTime)>::new();
+
+        // Send every command
+        for (i, command) in commands.iter().enumerate() {
+            // Encode the command so that we know its id
+            let encoded = match command.encode_request() {
+                Ok(v) => v,
+                Err(e) => {
+                    results[i] = Some(Err(e));
+                    continue;
+                }
+            };
+            let id = crate::devices::request_command_id(&encoded);
+
+            match self.send_encoded(encoded, command.modifies_device()) {
+                // Commands that will not be responded to are decoded immediately
+                Ok(_) if !command.expects_response() => results[i] = Some(command.decode_response(id.0, Vec::new())),
+                Ok(_) => pending.push((i, id, std::time::SystemTime::now() + timeout)),
+                Err(e) => results[i] = Some(Err(e)),
+            }
+        }
+
+        // Recieve responses until every command has been answered or has timed out
+        while let Some(deadline) = pending.iter().map(|p| p.2).min() {
+            // Wait no longer than the earliest deadline
+            let remaining = deadline.duration_since(std::time::SystemTime::now()).unwrap_or_default();
+
+            match self.receive_packet(remaining) {
+                Ok((command, payload)) => {
+                    // Find the oldest outstanding command this is a response to.
+                    // If there is none, then the response is discarded
+                    let id = crate::devices::response_command_id(command, &payload);
+                    if let Some(pos) = pending.iter().position(|p| p.1 == id) {
+                        let (i, _, _) = pending.remove(pos);
+                        results[i] = Some(commands[i].decode_response(command, payload));
+                    }
+                },
+                Err(crate::errors::DecodeError::HeaderTimeout) => {
+                    // Fail every command whose deadline has passed
+                    let now = std::time::SystemTime::now();
+                    pending.retain(|p| {
+                        if p.2 <= now {
+                            results[p.0] = Some(Err(crate::errors::DecodeError::HeaderTimeout));
+                            false
+                        } else {
+                            true
+                        }
+                    });
+                },
+                Err(crate::errors::DecodeError::IoError(e)) => {
+                    // The port itself failed, so no more responses will arrive
+                    for (i, _, _) in pending.drain(..) {
+                        results[i] = Some(Err(crate::errors::DecodeError::IoError(std::io::Error::new(e.kind(), e.to_string()))));
+                    }
+                },
+                Err(_) => {
+                    // A malformed packet can not be matched to a command, so it is discarded like any other unmatched response
+                }
+            }
+        }
+
+        // Every command has a result by now
+        results.into_iter().map(|r| r.unwrap_or(Err(crate::errors::DecodeError::HeaderTimeout))).collect()
+    }
+
     /// Recieves a response for a command
+    /// 
+    /// Packets that the command rejects as responses to other commands are discarded, so a late response to an earlier command
+    /// does not desync the stream. If no matching response arrives within the timeout, [crate::errors::DecodeError::HeaderTimeout] is returned.
+    /// 
+    /// Without the command that was sent, only the simple command id can be checked, so a late extended response can still be
+    /// decoded as this command's response (or returned as its NACK). [Self::response_to] checks the extended command id too.
     pub fn response_for<C: crate::commands::Command + Copy>(&mut self, timeout: std::time::Duration) -> Result<C::Response, crate::errors::DecodeError> {
+        self.response_matching::<C>(None, timeout)
+    }
+
+    /// Recieves the response to `command`, which has already been sent
+    /// 
+    /// Any packet whose simple or extended command id does not match the command's is discarded before it is decoded,
+    /// so stray packets (such as a late response or NACK to an earlier command that timed out) do not fail this command.
+    /// If no matching response arrives within the timeout, [crate::errors::DecodeError::HeaderTimeout] is returned.
+    pub fn response_to<C: crate::commands::Command + Copy>(&mut self, command: C, timeout: std::time::Duration) -> Result<C::Response, crate::errors::DecodeError> {
+        let id = crate::devices::request_command_id(&command.encode_request()?);
+
+        self.response_matching::<C>(Some(id), timeout)
+    }
+
+    /// Recieves a response for a command, discarding packets that do not have the command's id (if it is given)
+    /// or that the command rejects as responses to other commands
+    fn response_matching<C: crate::commands::Command + Copy>(&mut self, id: Option<(u8, Option<u8>)>, timeout: std::time::Duration) -> Result<C::Response, crate::errors::DecodeError> {
+        // The whole response needs to arrive within the timeout, even if other packets arrive first
+        let deadline = std::time::Instant::now() + timeout;
+
+        loop {
+            // Recieve the packet with whatever time is left
+            let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+            let (command, payload) = self.receive_packet(remaining)?;
+
+            // Skip packets that belong to other commands before decoding them, so that their NACKs are not mistaken for ours
+            if id.is_some_and(|id| crate::devices::response_command_id(command, &payload) != id) {
+                continue;
+            }
+
+            match C::decode_response(command, payload) {
+                // This was a response to some other command (such as a late response to an earlier command that timed out).
+                // Its bytes have already been consumed, so skip it and wait for the next packet.
+                Err(crate::errors::DecodeError::ExpectedCommand(_, _)) => continue,
+                result => return result,
+            }
+        }
+    }
+
+    /// Recieves a single packet without decoding it, returning its framing along with its simple command id and payload
+    /// 
+    /// This is the same as [Self::receive_packet], but makes it clear whether the device used simple or extended framing,
+    /// which is useful when diagnosing a device that responds with a different framing than expected.
+    pub fn receive_framed_packet(&mut self, timeout: std::time::Duration) -> Result<(super::ResponseFraming, u8, Vec<u8>), crate::errors::DecodeError> {
+        let (command_id, payload) = self.receive_packet(timeout)?;
+
+        Ok((super::ResponseFraming::from_command_id(command_id), command_id, payload))
+    }
+
+    /// Fills `buf` from the system port, returning [crate::errors::DecodeError::HeaderTimeout] if it is not filled by `deadline`.
+    /// 
+    /// Reads that time out are retried until the deadline, so the port's own read timeout only limits how late the deadline is noticed.
+    /// Ports opened from a [super::VexDevice] wait at most [super::SERIAL_POLL_TIMEOUT_MS] for each read.
+    fn read_exact_until(&mut self, buf: &mut [u8], deadline: std::time::Instant) -> Result<(), crate::errors::DecodeError> {
+        let mut filled = 0;
+
+        while filled < buf.len() {
+            match self.system_port.read(&mut buf[filled..]) {
+                Ok(0) => return Err(crate::errors::DecodeError::IoError(std::io::ErrorKind::UnexpectedEof.into())),
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => (),
+                Err(e) if e.kind() == std::io::ErrorKind::TimedOut || e.kind() == std::io::ErrorKind::WouldBlock => {
+                    if std::time::Instant::now() >= deadline {
+                        return Err(crate::errors::DecodeError::HeaderTimeout);
+                    }
+                }
+                Err(e) => return Err(crate::errors::DecodeError::IoError(e)),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Recieves a single packet without decoding it, returning its simple command id and payload
+    /// 
+    /// `timeout` only bounds how long to wait for the packet's header. Once the header has arrived, the rest of
+    /// the packet is given up to a second of its own, so this can return up to a second after `timeout`.
+    pub fn receive_packet(&mut self, timeout: std::time::Duration) -> Result<(u8, Vec<u8>), crate::errors::DecodeError> {
         // We need to wait to recieve the header of a packet.
         // The header should be the bytes [0xAA, 0x55]
 
         // This header needs to be recieved within the timeout.
         // If it is not recieved within the timeout, then we need to return an error.
         // Begin the countdown now:
-        let countdown = std::time::SystemTime::now() + timeout;
+        let countdown = std::time::Instant::now() + timeout;
 
         // Create a buffer for the header bytes
         // This is configurable just in case vex changes the header bytes on us.
@@ -101,16 +638,13 @@ impl<S: Read + Write, U: Read+Write> Device<S, U> {
             // If the timeout has elapsed, then we need to return an error.
             // We need to do this first just in case we actually do recieve the header
             // before the timeout has elapsed.
-            if countdown < std::time::SystemTime::now() {
+            if countdown < std::time::Instant::now() {
                 return Err(crate::errors::DecodeError::HeaderTimeout);
             }
 
             // Recieve a single bytes
             let mut b: [u8; 1] = [0];
-            match self.system_port.read_exact(&mut b) { // Do some match magic to convert the error types
-                Ok(v) => Ok(v),
-                Err(e) => Err(crate::errors::DecodeError::IoError(e)),
-            }?;
+            self.read_exact_until(&mut b, countdown)?;
             let b = b[0];
             
 
@@ -124,15 +658,15 @@ impl<S: Read + Write, U: Read+Write> Device<S, U> {
         
         // Now that we know we have recieved the header, we need to recieve the rest of the packet.
 
+        // The rest of the packet gets its own timeout, so that a packet whose header arrives just before the deadline is not cut off
+        let body_deadline = std::time::Instant::now() + PACKET_BODY_TIMEOUT;
+
         // First create a vector containing the entirety of the recieved packet
         let mut packet: Vec<u8> = Vec::from(expected_header);
 
         // Read int he next two bytes
         let mut b: [u8; 2] = [0; 2];
-        match self.system_port.read_exact(&mut b) { // Do some match magic to convert the error types
-            Ok(v) => Ok(v),
-            Err(e) => Err(crate::errors::DecodeError::IoError(e)),
-        }?;
+        self.read_exact_until(&mut b, body_deadline)?;
         packet.extend_from_slice(&b);
 
         // Get the command byte and the length byte of the packet
@@ -140,13 +674,12 @@ impl<S: Read + Write, U: Read+Write> Device<S, U> {
         
         // We may need to modify the length of the packet if it is an extended command
         // Extended commands use a u16 instead of a u8 for the length.
+        // If the top bit of the first length byte is set, the remaining 7 bits are the high byte,
+        // so lengths of 0x80 and up always take two bytes. This matches Extended::encode_request.
         let length = if 0x56 == command && b[1] & 0x80 == 0x80 {
             // Read the lower bytes
             let mut bl: [u8; 1] = [0];
-            match self.system_port.read_exact(&mut bl) { // Do some match magic to convert the error types
-                Ok(v) => Ok(v),
-                Err(e) => Err(crate::errors::DecodeError::IoError(e)),
-            }?;
+            self.read_exact_until(&mut bl, body_deadline)?;
             packet.push(bl[0]);
 
             (((b[1] & 0x7f) as u16) << 8) | (bl[0] as u16)
@@ -158,23 +691,664 @@ impl<S: Read + Write, U: Read+Write> Device<S, U> {
         let mut payload: Vec<u8> = vec![0; length as usize];
         // DO NOT CHANGE THIS TO READ. read_exact is required to suppress
         // CRC errors and missing data.
-        match self.system_port.read_exact(&mut payload) { // Do some match magic to convert the error types
-            Ok(v) => Ok(v),
-            Err(e) => Err(crate::errors::DecodeError::IoError(e)),
-        }?;
+        self.read_exact_until(&mut payload, body_deadline)?;
         packet.extend(&payload);
+
+        if let Some(stats) = &mut self.stats {
+            stats.bytes_rx += packet.len() as u64;
+        }
+
+        if let Some(tracer) = &self.packet_tracer {
+            tracer(super::PacketDirection::Received, &packet);
+        }
+
+        // Let the ack observer see the ack of extended responses
+        if let (Some(observer), 0x56, Some(ack)) = (&self.ack_observer, command, payload.get(1)) {
+            if let Ok(ack_type) = crate::errors::VexACKType::from_u8(*ack) {
+                observer(*ack, ack_type);
+            }
+        }
         
-        C::decode_response(command, payload)
+        Ok((command, payload))
+    }
+
+    /// Returns true if the given key exists in the brain's key-value store.
+    /// 
+    /// The brain responds to a read of a key that does not exist with a general NACK, which is mapped to `false`.
+    /// Any other error is returned.
+    pub fn kv_exists(&mut self, key: &str) -> Result<bool, crate::errors::DecodeError> {
+        Ok(self.kv_read_optional(key)?.is_some())
+    }
+
+    /// Reads several key-value entries from the brain, returning each key with its value.
+    /// 
+    /// Keys that do not exist on the brain have a value of None. Any other error stops the reads and is returned.
+    pub fn kv_read_many(&mut self, keys: &[&str]) -> Result<Vec<(String, Option<String>)>, crate::errors::DecodeError> {
+        let mut values = Vec::with_capacity(keys.len());
+
+        for key in keys {
+            values.push((key.to_string(), self.kv_read_optional(key)?));
+        }
+
+        Ok(values)
+    }
+
+    /// Reads a key-value entry from the brain and parses it into `T`.
+    /// 
+    /// Values are stored as strings, so this is useful for keys that hold numbers or flags.
+    /// If the value can not be parsed, then [crate::errors::DecodeError::InvalidValue] is returned.
+    pub fn kv_read_as<T: std::str::FromStr>(&mut self, key: &str) -> Result<T, crate::errors::DecodeError> {
+        let value = self.send_request(crate::commands::KVRead(key))?;
+
+        value.trim().parse().map_err(|_| crate::errors::DecodeError::InvalidValue(format!("value {:?} of key {:?} could not be parsed", value, key)))
+    }
+
+    /// Reads a key-value entry from the brain, mapping the general NACK returned for missing keys to None
+    fn kv_read_optional(&mut self, key: &str) -> Result<Option<String>, crate::errors::DecodeError> {
+        match self.send_request(crate::commands::KVRead(key)) {
+            Ok(value) => Ok(Some(value)),
+            Err(crate::errors::DecodeError::NACK(crate::errors::VexACKType::NACKGeneral)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Writes a key-value entry to the brain, then reads it back to confirm the write.
+    /// 
+    /// Returns the value the brain holds after the write. This may differ from `value` if the brain
+    /// did not accept the write, or if the value was truncated to the maximum length of the key.
+    /// If the brain refuses the write with a NACK, then that error is returned.
+    pub fn kv_write_confirmed(&mut self, key: &str, value: &str) -> Result<String, crate::errors::DecodeError> {
+        // Write the value
+        self.send_request(crate::commands::KVWrite(key, value))?;
+
+        // And read it back
+        self.send_request(crate::commands::KVRead(key))
+    }
+
+    /// Returns true if a file with the given name exists on the brain.
+    /// 
+    /// The brain responds to a metadata request for a file that does not exist with
+    /// [crate::errors::VexACKType::NACKDirectoryNoExist], which is mapped to `false`. Any other error is returned.
+    pub fn file_exists(&mut self, name: &str, vid: crate::v5::FileTransferVID) -> Result<bool, crate::errors::DecodeError> {
+        let name = crate::commands::encode_file_name(name)?;
+
+        match self.send_request(crate::commands::GetFileMetadataByName(&name, vid, crate::v5::FileTransferOptions::NONE)) {
+            Ok(_) => Ok(true),
+            Err(crate::errors::DecodeError::NACK(crate::errors::VexACKType::NACKDirectoryNoExist)) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Returns the number of files stored under each known VID.
+    /// 
+    /// VIDs that the brain NACKs are left out of the summary.
+    pub fn directory_summary(&mut self) -> Result<Vec<(crate::v5::FileTransferVID, i16)>, crate::errors::DecodeError> {
+        use crate::v5::FileTransferVID;
+
+        let mut summary = Vec::new();
+
+        for vid in [FileTransferVID::User, FileTransferVID::System, FileTransferVID::RMS, FileTransferVID::PROS, FileTransferVID::MW] {
+            match self.send_request(crate::commands::GetDirectoryCount(vid, crate::v5::FileTransferOptions::NONE)) {
+                Ok(count) => summary.push((vid, count)),
+                // Skip VIDs that the brain does not know about
+                Err(crate::errors::DecodeError::NACK(_)) => (),
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(summary)
+    }
+
+    /// Lists the files stored under a VID
+    pub fn list_files(&mut self, vid: crate::v5::FileTransferVID) -> Result<Vec<crate::v5::FileInfo>, crate::errors::DecodeError> {
+        self.file_iter(vid)?.collect()
+    }
+
+    /// Returns an iterator over the files stored under a VID, which reads the metadata of one file each time it is advanced.
+    /// 
+    /// The number of files is read up front, so the iterator knows when to stop. It stops after the first error.
+    /// Only the first 256 files of a directory can be read by index, so a larger directory ends with an error after its 256th file.
+    /// Sending other commands to the device while iterating could change the directory that the files are read from,
+    /// so the iterator holds the device until it is dropped.
+    pub fn file_iter(&mut self, vid: crate::v5::FileTransferVID) -> Result<FileIter<'_, S, U>, crate::errors::DecodeError> {
+        // Getting the count selects the directory that the files are read from
+        let count = self.send_request(crate::commands::GetDirectoryCount(vid, crate::v5::FileTransferOptions::NONE))?;
+
+        Ok(FileIter {
+            device: self,
+            index: 0,
+            count: count.max(0) as u16,
+        })
+    }
+
+    /// Erases a file from the brain
+    pub fn erase_file(&mut self, name: &str, vid: crate::v5::FileTransferVID) -> Result<(), crate::errors::DecodeError> {
+        let name = crate::commands::encode_file_name(name)?;
+
+        self.erase_file_raw(name, vid)
+    }
+
+    /// Erases a file from the brain by its name exactly as the brain stores it
+    fn erase_file_raw(&mut self, name: [u8; 24], vid: crate::v5::FileTransferVID) -> Result<(), crate::errors::DecodeError> {
+        self.send_request(crate::commands::EraseFile(name, vid, crate::v5::FileTransferOptions::NONE))?;
+
+        // The erase is completed by exiting the transfer
+        self.send_request(crate::commands::FileTransferExit(crate::v5::FileTransferComplete::DoNothing))
+    }
+
+    /// Reads the metadata of every file stored under a VID, keeping each name exactly as the brain stores it
+    fn raw_file_metadata(&mut self, vid: crate::v5::FileTransferVID) -> Result<Vec<crate::v5::FileMetadataByIndex>, crate::errors::DecodeError> {
+        // Getting the count selects the directory that the files are read from
+        let count = self.send_request(crate::commands::GetDirectoryCount(vid, crate::v5::FileTransferOptions::NONE))?.max(0);
+
+        // File indices are a single byte, so only the first 256 files of a directory can be addressed
+        let unaddressable = || crate::errors::DecodeError::InvalidValue(format!("the directory has {count} files, but only 256 can be read by index"));
+        if count > 256 {
+            return Err(unaddressable());
+        }
+
+        let mut files = Vec::with_capacity(count as usize);
+        for index in 0..count {
+            let index = u8::try_from(index).map_err(|_| unaddressable())?;
+            files.push(self.send_request(crate::commands::GetFileMetadataByIndex(index, crate::v5::FileTransferOptions::NONE))?);
+        }
+
+        Ok(files)
+    }
+
+    /// Erases every file on the brain, under every known VID.
+    /// 
+    /// VexOS has no known command for formatting the filesystem, so this lists the files of each VID and erases them one by one.
+    /// This can not be undone, so `confirm` must be true, and [crate::errors::DecodeError::InvalidValue] is returned otherwise.
+    /// 
+    /// Files are erased by their names exactly as the brain stores them, so files with corrupted names are erased too.
+    /// A file that fails to erase does not stop the format. Instead, the name of each file that could not be erased is returned
+    /// along with its error. Errors while listing the files are still returned immediately.
+    pub fn format_filesystem(&mut self, confirm: bool) -> Result<Vec<(String, crate::errors::DecodeError)>, crate::errors::DecodeError> {
+        if !confirm {
+            return Err(crate::errors::DecodeError::InvalidValue("format_filesystem must be confirmed".to_string()));
+        }
+
+        let mut failures = Vec::new();
+
+        for (vid, _) in self.directory_summary()? {
+            // List the files first, since erasing changes the indexes of the remaining files
+            for file in self.raw_file_metadata(vid)? {
+                if let Err(e) = self.erase_file_raw(file.name, vid) {
+                    failures.push((crate::commands::decode_file_name(&file.name), e));
+                }
+            }
+        }
+
+        Ok(failures)
+    }
+
+    /// Starts building a file transfer, as an alternative to filling out a [crate::commands::FileTransferInit] by hand.
+    /// 
+    /// Uploads are run with [Device::upload_file], and downloads with [Device::download_file].
+    /// For example, `device.transfer().upload().name("slot_1.bin").data(&binary).on_complete(FileTransferComplete::RunProgram).run()`.
+    pub fn transfer(&mut self) -> TransferBuilder<'_, S, U> {
+        TransferBuilder { device: self }
+    }
+
+    /// Initializes a file transfer, returning a guard that exits the transfer when it is dropped.
+    /// 
+    /// Reads and writes for the transfer should be sent through the guard, and the transfer should be
+    /// completed with [TransferGuard::finish]. If this device is a wireless controller, it is switched to the download channel
+    /// (and left on it) first, since transfers can not reach the brain otherwise.
+    pub fn begin_transfer(&mut self, init: crate::commands::FileTransferInit) -> Result<TransferGuard<'_, S, U>, crate::errors::DecodeError> {
+        if self.is_wireless_controller()? {
+            self.send_request(crate::commands::SwitchChannel(crate::v5::V5ControllerChannel::Download))?;
+        }
+
+        // Initialize the transfer
+        let response = self.send_request(init)?;
+
+        Ok(TransferGuard {
+            device: self,
+            response,
+            finished: false,
+        })
+    }
+
+    /// Reads the output of the user program until no new data arrives for `idle_timeout`.
+    /// 
+    /// Data is read from the user port if it is available, and over the system port if it is not.
+    /// Because some programs never stop printing, reading also stops once `max_bytes` have been read (if it is provided).
+    pub fn read_program_output(&mut self, idle_timeout: std::time::Duration, max_bytes: Option<usize>) -> Result<Vec<u8>, crate::errors::DecodeError> {
+        let mut output = Vec::<u8>::new();
+        let mut buf = [0u8; 0x40];
+
+        // The last time that we recieved any data
+        let mut last_data = std::time::SystemTime::now();
+
+        loop {
+            // Never read past max_bytes
+            let len = match max_bytes {
+                Some(max) if output.len() >= max => break,
+                Some(max) => usize::min(buf.len(), max - output.len()),
+                None => buf.len(),
+            };
+
+            // Read from the user port if we can, treating a port timeout as no data
+            let n = if let Some(p) = &mut self.user_port {
+                match p.read(&mut buf[..len]) {
+                    Ok(n) => n,
+                    Err(e) if e.kind() == std::io::ErrorKind::TimedOut || e.kind() == std::io::ErrorKind::WouldBlock => 0,
+                    Err(e) => return Err(crate::errors::DecodeError::IoError(e)),
+                }
+            } else {
+                self.read_serial(&mut buf[..len])?
+            };
+
+            if n > 0 {
+                output.extend(&buf[..n]);
+                last_data = std::time::SystemTime::now();
+            } else if last_data.elapsed().unwrap_or_default() >= idle_timeout {
+                // If nothing has arrived for long enough, then the program is done
+                break;
+            }
+        }
+
+        Ok(output)
+    }
+
+    /// Uploads a file to the brain's flash, overwriting any file with the same name.
+    /// 
+    /// The file is written to the default address for flash (see [crate::commands::FileTransferInit::default_addr]),
+    /// timestamped with the current time, and given version 1.0.0.0. `on_complete` is the action the brain takes once
+    /// the upload has finished, such as running the uploaded program.
+    /// 
+    /// Returns the brain's response to initializing the transfer, which includes the file size and CRC it agreed to.
+    pub fn upload_file(&mut self, name: &str, vid: crate::v5::FileTransferVID, file_type: crate::v5::FileTransferType, data: &[u8], on_complete: crate::v5::FileTransferComplete) -> Result<crate::commands::FileTransferInitResponse, crate::errors::DecodeError> {
+        let timestamp = crate::v5::system_time_to_v5_timestamp(std::time::SystemTime::now());
+
+        self.upload_file_with_metadata(name, vid, file_type, timestamp, 0x01000000, data, on_complete)
+    }
+
+    /// Installs a program into a slot (1-8), uploading the binary as `slot_N.bin` and an ini file describing it as `slot_N.ini`.
+    /// 
+    /// The ini file holds the name and description that the brain shows for the program, and is tied to the binary by its name.
+    /// If the ini file fails to upload, the binary is erased again so that the slot is not left half installed.
+    pub fn install_program(&mut self, slot: u8, binary: &[u8], name: &str, description: &str) -> Result<(), crate::errors::DecodeError> {
+        let bin_name = super::slot_file_name(slot, "bin")?;
+        let ini_name = super::slot_file_name(slot, "ini")?;
+        let ini = super::program_ini(slot, name, description)?;
+        let vid = crate::v5::FileTransferVID::User;
+
+        self.upload_file(&bin_name, vid, crate::v5::FileTransferType::Bin, binary, crate::v5::FileTransferComplete::DoNothing)?;
+
+        if let Err(e) = self.upload_file(&ini_name, vid, crate::v5::FileTransferType::Ini, ini.as_bytes(), crate::v5::FileTransferComplete::DoNothing) {
+            // Roll back the binary. This is best-effort, since the ini error is the one worth returning.
+            let _ = self.erase_file(&bin_name, vid);
+            return Err(e);
+        }
+
+        Ok(())
+    }
+
+    /// Copies a file from one VID to another, keeping its type, timestamp, and version.
+    /// 
+    /// The file is downloaded and then uploaded again, so this takes as long as both transfers.
+    /// If a file with the same name already exists under `to`, it is overwritten.
+    pub fn copy_file(&mut self, name: &str, from: crate::v5::FileTransferVID, to: crate::v5::FileTransferVID) -> Result<(), crate::errors::DecodeError> {
+        // Get the metadata to keep
+        let encoded = crate::commands::encode_file_name(name)?;
+        let metadata = self.send_request(crate::commands::GetFileMetadataByName(&encoded, from, crate::v5::FileTransferOptions::NONE))?;
+
+        let data = self.download_file(name, from)?;
+
+        self.upload_file_with_metadata(name, to, metadata.file_type, metadata.timestamp, metadata.version, &data, crate::v5::FileTransferComplete::DoNothing)?;
+
+        Ok(())
+    }
+
+    /// Uploads a file to the brain's flash from a reader, overwriting any file with the same name.
+    /// 
+    /// This works like [Device::upload_file], but only holds one chunk of the file in memory at a time, so it can upload
+    /// files that are too large to read into memory. The brain needs the length and CRC32 (see [crate::VEX_CRC32]) of the file
+    /// before any data is sent, so both must be known up front. If the reader ends before `length` bytes have been read,
+    /// the transfer is abandoned and [crate::errors::DecodeError::IoError] is returned.
+    #[allow(clippy::too_many_arguments)]
+    pub fn upload_from_reader(&mut self, name: &str, vid: crate::v5::FileTransferVID, file_type: crate::v5::FileTransferType, length: u32, crc: u32, reader: &mut impl std::io::Read, on_complete: crate::v5::FileTransferComplete) -> Result<crate::commands::FileTransferInitResponse, crate::errors::DecodeError> {
+        let timestamp = crate::v5::system_time_to_v5_timestamp(std::time::SystemTime::now());
+        let init = super::upload_init(name, vid, file_type, length, crc, timestamp, 0x01000000)?;
+
+        self.upload_with_init(init, reader, on_complete)
+    }
+
+    /// Uploads a file to the brain's flash with the given metadata, overwriting any file with the same name,
+    /// and returns the brain's response to initializing the transfer
+    #[allow(clippy::too_many_arguments)]
+    fn upload_file_with_metadata(&mut self, name: &str, vid: crate::v5::FileTransferVID, file_type: crate::v5::FileTransferType, timestamp: u32, version: u32, data: &[u8], on_complete: crate::v5::FileTransferComplete) -> Result<crate::commands::FileTransferInitResponse, crate::errors::DecodeError> {
+        // The length is sent as a u32
+        let length = u32::try_from(data.len()).map_err(|_| crate::errors::DecodeError::InvalidValue("file is too large to upload".to_string()))?;
+        let crc = crc::Crc::<u32>::new(&crate::VEX_CRC32).checksum(data);
+        let init = super::upload_init(name, vid, file_type, length, crc, timestamp, version)?;
+
+        let mut reader = data;
+        self.upload_with_init(init, &mut reader, on_complete)
+    }
+
+    /// Begins the transfer described by `init`, writes `init.length` bytes from `reader`, and exits the transfer,
+    /// returning the brain's response to initializing the transfer
+    fn upload_with_init(&mut self, init: crate::commands::FileTransferInit, reader: &mut impl std::io::Read, on_complete: crate::v5::FileTransferComplete) -> Result<crate::commands::FileTransferInitResponse, crate::errors::DecodeError> {
+        let addr = init.addr;
+        let length = init.length as usize;
+
+        // Begin the transfer
+        let mut transfer = self.begin_transfer(init)?;
+
+        // Write the file in chunks of the largest size the brain allows, keeping each chunk a multiple of four bytes
+        // so that the padding added to each write does not overlap the next one
+        let chunk_size = (transfer.response().max_packet_size as usize / 4) * 4;
+        if chunk_size == 0 {
+            return Err(crate::errors::DecodeError::PacketLengthError);
+        }
+
+        // Only one chunk is read from the reader at a time
+        let mut chunk = vec![0u8; chunk_size];
+        let mut offset = 0;
+        while offset < length {
+            let n = chunk_size.min(length - offset);
+            reader.read_exact(&mut chunk[..n])?;
+            transfer.write(addr + offset as u32, &chunk[..n])?;
+            offset += n;
+        }
+
+        // Exit the transfer, keeping the init response to return
+        let response = transfer.response();
+        transfer.finish(on_complete)?;
+
+        Ok(response)
+    }
+
+    /// Downloads a file from the brain's flash.
+    /// 
+    /// See [Device::download_file_partial] to keep the data that was read if the download fails partway through.
+    pub fn download_file(&mut self, name: &str, vid: crate::v5::FileTransferVID) -> Result<Vec<u8>, crate::errors::DecodeError> {
+        let mut data = Vec::<u8>::new();
+        self.download_file_into(name, vid, &mut data)?;
+
+        Ok(data)
+    }
+
+    /// Downloads a file from the brain's flash, returning the data that was read along with any error.
+    /// 
+    /// If the download fails partway through (for example, because of a timeout), the bytes that were read
+    /// before the failure are still returned so that they can be inspected, or so that the download can be resumed.
+    pub fn download_file_partial(&mut self, name: &str, vid: crate::v5::FileTransferVID) -> (Vec<u8>, Option<crate::errors::DecodeError>) {
+        let mut data = Vec::<u8>::new();
+
+        match self.download_file_into(name, vid, &mut data) {
+            Ok(_) => (data, None),
+            Err(e) => (data, Some(e)),
+        }
+    }
+
+    /// Downloads a file from the brain's flash, appending it to `data` as it is read
+    fn download_file_into(&mut self, name: &str, vid: crate::v5::FileTransferVID, data: &mut Vec<u8>) -> Result<(), crate::errors::DecodeError> {
+        // Encode the name
+        let name = crate::commands::encode_file_name(name)?;
+
+        // Find where the file is stored
+        let metadata = self.send_request(crate::commands::GetFileMetadataByName(&name, vid, crate::v5::FileTransferOptions::NONE))?;
+
+        self.download_with_init(crate::commands::FileTransferInit {
+            function: crate::v5::FileTransferFunction::Download,
+            target: crate::v5::FileTransferTarget::Flash,
+            vid,
+            options: crate::v5::FileTransferOptions::NONE,
+            file_type: metadata.file_type,
+            length: metadata.length,
+            addr: metadata.addr,
+            crc: metadata.crc,
+            timestamp: 0,
+            version: 0,
+            name,
+        }, data)
+    }
+
+    /// Downloads a file from the brain's flash by its index in the directory of a VID, without knowing its name.
+    /// 
+    /// This is useful for files found with [Device::file_iter] whose names do not survive being turned into a string,
+    /// such as names with bytes that are not valid UTF-8.
+    pub fn download_file_by_index(&mut self, index: u8, vid: crate::v5::FileTransferVID) -> Result<Vec<u8>, crate::errors::DecodeError> {
+        // Getting the count selects the directory that the file is read from
+        let count = self.send_request(crate::commands::GetDirectoryCount(vid, crate::v5::FileTransferOptions::NONE))?;
+        if index as i16 >= count {
+            return Err(crate::errors::DecodeError::InvalidValue(format!("file index {index} is out of range for a directory of {count} files")));
+        }
+
+        let metadata = self.send_request(crate::commands::GetFileMetadataByIndex(index, crate::v5::FileTransferOptions::NONE))?;
+
+        self.download_file_by_metadata(&metadata, vid)
+    }
+
+    /// Downloads a file from the brain's flash using the metadata read from its index, including its name exactly as the brain stores it
+    fn download_file_by_metadata(&mut self, metadata: &crate::v5::FileMetadataByIndex, vid: crate::v5::FileTransferVID) -> Result<Vec<u8>, crate::errors::DecodeError> {
+        let mut data = Vec::<u8>::new();
+        self.download_with_init(crate::commands::FileTransferInit {
+            function: crate::v5::FileTransferFunction::Download,
+            target: crate::v5::FileTransferTarget::Flash,
+            vid,
+            options: crate::v5::FileTransferOptions::NONE,
+            file_type: metadata.file_type,
+            length: metadata.length,
+            addr: metadata.addr,
+            crc: metadata.crc,
+            timestamp: 0,
+            version: 0,
+            name: metadata.name,
+        }, &mut data)?;
+
+        Ok(data)
+    }
+
+    /// Downloads a file along with every file it links to, returning the name and contents of each.
+    /// 
+    /// Links are followed from [crate::v5::FileMetadataByName::linked_filename] until a file with no link is reached,
+    /// and a file that has already been downloaded ends the chain, so a cycle of links can not loop forever.
+    /// A program's ini file is not linked to its binary, so if `name` ends in `.bin` and an ini file with the same name
+    /// exists, it is downloaded too. The first file in the result is always the one named by `name`.
+    pub fn download_program_bundle(&mut self, name: &str, vid: crate::v5::FileTransferVID) -> Result<Vec<(String, Vec<u8>)>, crate::errors::DecodeError> {
+        let mut files = Vec::<(String, Vec<u8>)>::new();
+        let mut visited = Vec::<(String, crate::v5::FileTransferVID)>::new();
+
+        let mut next = Some((name.to_string(), vid));
+        while let Some((name, vid)) = next.take() {
+            // Stop at a file that has already been downloaded
+            if visited.contains(&(name.clone(), vid)) {
+                break;
+            }
+            visited.push((name.clone(), vid));
+
+            let encoded = crate::commands::encode_file_name(&name)?;
+            let metadata = self.send_request(crate::commands::GetFileMetadataByName(&encoded, vid, crate::v5::FileTransferOptions::NONE))?;
+
+            // Follow the link, if there is one
+            let linked_name = crate::commands::decode_file_name(&metadata.linked_filename);
+            if !linked_name.is_empty() {
+                next = Some((linked_name, metadata.linked_vid));
+            }
+
+            let data = self.download_file(&name, vid)?;
+            files.push((name, data));
+        }
+
+        // Include the program's ini file
+        if let Some(stem) = name.strip_suffix(".bin") {
+            let ini_name = format!("{stem}.ini");
+            if !visited.contains(&(ini_name.clone(), vid)) && self.file_exists(&ini_name, vid)? {
+                let data = self.download_file(&ini_name, vid)?;
+                files.push((ini_name, data));
+            }
+        }
+
+        Ok(files)
+    }
+
+    /// Begins the download described by `init`, appending the file to `data` as it is read, and exits the transfer
+    fn download_with_init(&mut self, init: crate::commands::FileTransferInit, data: &mut Vec<u8>) -> Result<(), crate::errors::DecodeError> {
+        let addr = init.addr;
+
+        // Begin the transfer
+        let mut transfer = self.begin_transfer(init)?;
+
+        // Read the file in chunks of the largest size the brain allows
+        let max_packet_size = transfer.response().max_packet_size as u32;
+        let file_size = transfer.response().file_size;
+        if max_packet_size == 0 {
+            return Err(crate::errors::DecodeError::PacketLengthError);
+        }
+
+        let mut offset = 0u32;
+        while offset < file_size {
+            let nbytes = u32::min(max_packet_size, file_size - offset);
+            let chunk = transfer.read(addr + offset, nbytes as u16)?;
+
+            // Reads are padded to four bytes, so only keep the bytes that were requested
+            if chunk.len() < nbytes as usize {
+                return Err(crate::errors::DecodeError::PacketLengthError);
+            }
+            data.extend(&chunk[..nbytes as usize]);
+
+            offset += nbytes;
+        }
+
+        // Exit the transfer
+        transfer.finish(crate::v5::FileTransferComplete::DoNothing)
+    }
+
+    /// Returns the CRC32 (according to [crate::VEX_CRC32]) of the program in the given slot (1-8), as stored in its metadata.
+    /// 
+    /// This can be compared against the CRC of a local binary to skip uploading a program that has not changed.
+    pub fn program_crc(&mut self, slot: u8) -> Result<u32, crate::errors::DecodeError> {
+        let name = crate::commands::encode_file_name(&super::slot_file_name(slot, "bin")?)?;
+
+        let metadata = self.send_request(crate::commands::GetFileMetadataByName(&name, crate::v5::FileTransferVID::User, crate::v5::FileTransferOptions::NONE))?;
+
+        Ok(metadata.crc)
+    }
+
+    /// Downloads every file stored under a VID and checks it against the CRC32 the brain reports for it.
+    /// 
+    /// Returns each file's name with `true` if the CRC32 (according to [crate::VEX_CRC32]) of its downloaded data
+    /// matches its metadata, which can be used to find files that were corrupted in flash.
+    /// Files are downloaded by their names exactly as the brain stores them. A file that the brain refuses to read with a NACK,
+    /// or whose name is corrupted so badly that it can not be sent back (it has no null terminator), is also reported as `false`.
+    /// Any other error, such as a timeout or the device disconnecting, stops the check and is returned, since it says nothing
+    /// about the file itself. Every file is read in full, so this is slow.
+    pub fn verify_all_files(&mut self, vid: crate::v5::FileTransferVID) -> Result<Vec<(String, bool)>, crate::errors::DecodeError> {
+        let files = self.raw_file_metadata(vid)?;
+        let crc = crc::Crc::<u32>::new(&crate::VEX_CRC32);
+
+        let mut results = Vec::with_capacity(files.len());
+
+        for file in files {
+            // A name without a null terminator can not be sent in a transfer, so the file can not be read
+            if !file.name.contains(&0) {
+                results.push((crate::commands::decode_file_name(&file.name), false));
+                continue;
+            }
+
+            let matches = match self.download_file_by_metadata(&file, vid) {
+                Ok(data) => crc.checksum(&data) == file.crc,
+                Err(crate::errors::DecodeError::NACK(_)) => false,
+                Err(e) => return Err(e),
+            };
+
+            results.push((crate::commands::decode_file_name(&file.name), matches));
+        }
+
+        Ok(results)
+    }
+
+    /// Shows the run screen for the program in the given slot (1-8) without running it,
+    /// so that it is selected and ready for someone to press run on the brain.
+    /// 
+    /// VexOS does not have a command for selecting a slot, so this opens a download transfer of the slot's program
+    /// (`slot_N.bin` under [crate::v5::FileTransferVID::User]) and exits it with [crate::v5::FileTransferComplete::ShowRunScreen],
+    /// which is the same action used to show the run screen after an upload.
+    pub fn select_slot(&mut self, slot: u8) -> Result<(), crate::errors::DecodeError> {
+        let name = crate::commands::encode_file_name(&super::slot_file_name(slot, "bin")?)?;
+        let vid = crate::v5::FileTransferVID::User;
+
+        // Find where the program is stored
+        let metadata = self.send_request(crate::commands::GetFileMetadataByName(&name, vid, crate::v5::FileTransferOptions::NONE))?;
+
+        // Open a transfer of the program and immediately exit it, showing the run screen
+        let transfer = self.begin_transfer(crate::commands::FileTransferInit {
+            function: crate::v5::FileTransferFunction::Download,
+            target: crate::v5::FileTransferTarget::Flash,
+            vid,
+            options: crate::v5::FileTransferOptions::NONE,
+            file_type: metadata.file_type,
+            length: metadata.length,
+            addr: metadata.addr,
+            crc: metadata.crc,
+            timestamp: 0,
+            version: 0,
+            name,
+        })?;
+
+        transfer.finish(crate::v5::FileTransferComplete::ShowRunScreen)
+    }
+
+    /// Forcefully exits any file transfer that is in progress on the brain, such as one left open by another process.
+    /// 
+    /// This sends `FileTransferExit(DoNothing)` and ignores any NACK the brain responds with,
+    /// so it is safe to call even when no transfer is active. Transport errors are still returned.
+    pub fn force_reset_transfer(&mut self) -> Result<(), crate::errors::DecodeError> {
+        match self.send_request(crate::commands::FileTransferExit(crate::v5::FileTransferComplete::DoNothing)) {
+            Ok(_) | Err(crate::errors::DecodeError::NACK(_)) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Writes to the user program serial port over the system port, such as to the program's stdin over a wireless connection.
+    /// 
+    /// This uses the same command as [Self::read_serial], which the brain treats as a write when data follows the length.
+    /// The data is sent in chunks of up to 64 bytes. The brain may respond to a write with no data, which is not an error.
+    /// 
+    /// Returns [crate::errors::DeviceError::UnknownDeviceType] if the type of the device is [super::VexDeviceType::Unknown].
+    pub fn write_serial(&mut self, data: &[u8]) -> Result<(), crate::errors::DecodeError> {
+        // A device of unknown type may not have a user program to write to
+        if matches!(self.device_type, Some(super::VexDeviceType::Unknown)) {
+            return Err(crate::errors::DeviceError::UnknownDeviceType.into());
+        }
+
+        for chunk in data.chunks(0x40) {
+            // The channel, the length of the data, then the data itself
+            let mut payload = vec![crate::v5::V5ControllerChannel::Download as u8, chunk.len() as u8];
+            payload.extend(chunk);
+
+            let res = self.send_request(crate::commands::Extended(0x27, &payload))?;
+
+            // Ensure that the response is for the correct command. Its payload, if there is one, is not needed.
+            if res.0 != 0x27 {
+                return Err(crate::errors::DecodeError::ExpectedCommand(0x27, res.0));
+            }
+        }
+
+        Ok(())
     }
 
     /// Reads from the user program serial port over the system port
+    /// 
+    /// Returns [crate::errors::DeviceError::UnknownDeviceType] if the type of the device is [super::VexDeviceType::Unknown].
     pub fn read_serial(&mut self, buf: &mut [u8]) -> Result<usize, crate::errors::DecodeError> {
+        // A device of unknown type may not have a user program to read from
+        if matches!(self.device_type, Some(super::VexDeviceType::Unknown)) {
+            return Err(crate::errors::DeviceError::UnknownDeviceType.into());
+        }
+
         
         // Optimization: Only read more bytes from the brain if we need them. This allows usages
         // that use small reads to be much faster.
         if self.read_buffer.len() < buf.len() {
             // Form a custom Extended command to read and write from serial.
-            // We do the same as PROS, reading 64 bytes and specifying upload channel
+            // We do the same as PROS, reading 64 bytes on the download channel
             // Except we only read up to 64 bytes at a time, so that the user can configure if they want to 
             // read smaller chunks (and thus bypass CRC errors from packet corruption, at the expense of speed)
             let payload = vec![crate::v5::V5ControllerChannel::Download as u8, u8::min(0x40, self.user_read_size)];
@@ -188,8 +1362,11 @@ impl<S: Read + Write, U: Read+Write> Device<S, U> {
             }
 
             // The response payload should be the data that we read, so copy it into the read buffer
-            // Discarding the first byte like pros does
-            self.read_buffer.extend(&res.1[1..]);
+            // Discarding the first byte like pros does.
+            // An empty payload means that no data was available, in which case nothing is added and zero bytes are read.
+            if let Some(data) = res.1.get(1..) {
+                self.read_buffer.extend(data);
+            }
 
         }
 
@@ -215,6 +1392,222 @@ impl<S: Read + Write, U: Read+Write> Device<S, U> {
 
 }
 
+/// Iterates over the files stored under a VID, reading the metadata of one file at a time.
+/// 
+/// Created with [Device::file_iter].
+pub struct FileIter<'a, S: Read + Write, U: Read + Write> {
+    device: &'a mut Device<S, U>,
+    index: u16,
+    count: u16,
+}
+
+impl<'a, S: Read + Write, U: Read + Write> FileIter<'a, S, U> {
+    /// Returns the number of files under the VID, including any that have already been read
+    pub fn total(&self) -> u16 {
+        self.count
+    }
+}
+
+impl<'a, S: Read + Write, U: Read + Write> Iterator for FileIter<'a, S, U> {
+    type Item = Result<crate::v5::FileInfo, crate::errors::DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.count {
+            return None;
+        }
+
+        // File indices are a single byte, so files past the first 256 can not be read
+        let index = match u8::try_from(self.index) {
+            Ok(index) => index,
+            Err(_) => {
+                self.index = self.count;
+                return Some(Err(crate::errors::DecodeError::InvalidValue(format!("the directory has {} files, but only 256 can be read by index", self.count))));
+            }
+        };
+
+        match self.device.send_request(crate::commands::GetFileMetadataByIndex(index, crate::v5::FileTransferOptions::NONE)) {
+            Ok(metadata) => {
+                self.index += 1;
+                Some(Ok(metadata.into()))
+            }
+            Err(e) => {
+                // Stop after an error, since the rest of the files can not be trusted to be read correctly
+                self.index = self.count;
+                Some(Err(e))
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = (self.count - self.index) as usize;
+        (0, Some(remaining))
+    }
+}
+
+/// Chooses the direction of a file transfer. Created with [Device::transfer].
+pub struct TransferBuilder<'a, S: Read + Write, U: Read + Write> {
+    device: &'a mut Device<S, U>,
+}
+
+impl<'a, S: Read + Write, U: Read + Write> TransferBuilder<'a, S, U> {
+    /// Builds an upload to the brain's flash
+    pub fn upload(self) -> UploadBuilder<'a, S, U> {
+        UploadBuilder {
+            device: self.device,
+            name: None,
+            vid: crate::v5::FileTransferVID::User,
+            file_type: crate::v5::FileTransferType::Bin,
+            data: &[],
+            on_complete: crate::v5::FileTransferComplete::DoNothing,
+        }
+    }
+
+    /// Builds a download from the brain's flash
+    pub fn download(self) -> DownloadBuilder<'a, S, U> {
+        DownloadBuilder {
+            device: self.device,
+            name: None,
+            vid: crate::v5::FileTransferVID::User,
+        }
+    }
+}
+
+/// Builds an upload to the brain's flash.
+/// 
+/// The file is uploaded under the user VID as a binary, and the brain does nothing once it has finished, unless these are changed.
+/// The name must always be set.
+pub struct UploadBuilder<'a, S: Read + Write, U: Read + Write> {
+    device: &'a mut Device<S, U>,
+    name: Option<&'a str>,
+    vid: crate::v5::FileTransferVID,
+    file_type: crate::v5::FileTransferType,
+    data: &'a [u8],
+    on_complete: crate::v5::FileTransferComplete,
+}
+
+impl<'a, S: Read + Write, U: Read + Write> UploadBuilder<'a, S, U> {
+    /// Sets the name of the file
+    pub fn name(mut self, name: &'a str) -> Self {
+        self.name = Some(name);
+        self
+    }
+
+    /// Sets the VID to upload the file under
+    pub fn vid(mut self, vid: crate::v5::FileTransferVID) -> Self {
+        self.vid = vid;
+        self
+    }
+
+    /// Sets the type of the file
+    pub fn file_type(mut self, file_type: crate::v5::FileTransferType) -> Self {
+        self.file_type = file_type;
+        self
+    }
+
+    /// Sets the contents of the file
+    pub fn data(mut self, data: &'a [u8]) -> Self {
+        self.data = data;
+        self
+    }
+
+    /// Sets the action the brain takes once the upload has finished
+    pub fn on_complete(mut self, on_complete: crate::v5::FileTransferComplete) -> Self {
+        self.on_complete = on_complete;
+        self
+    }
+
+    /// Runs the upload, returning the brain's response to initializing the transfer.
+    /// 
+    /// Returns [crate::errors::DecodeError::InvalidValue] if no name was set.
+    pub fn run(self) -> Result<crate::commands::FileTransferInitResponse, crate::errors::DecodeError> {
+        let name = self.name.ok_or_else(|| crate::errors::DecodeError::InvalidValue("no file name was given for the upload".to_string()))?;
+
+        self.device.upload_file(name, self.vid, self.file_type, self.data, self.on_complete)
+    }
+}
+
+/// Builds a download from the brain's flash.
+/// 
+/// The file is downloaded from the user VID unless this is changed. The name must always be set.
+pub struct DownloadBuilder<'a, S: Read + Write, U: Read + Write> {
+    device: &'a mut Device<S, U>,
+    name: Option<&'a str>,
+    vid: crate::v5::FileTransferVID,
+}
+
+impl<'a, S: Read + Write, U: Read + Write> DownloadBuilder<'a, S, U> {
+    /// Sets the name of the file
+    pub fn name(mut self, name: &'a str) -> Self {
+        self.name = Some(name);
+        self
+    }
+
+    /// Sets the VID to download the file from
+    pub fn vid(mut self, vid: crate::v5::FileTransferVID) -> Self {
+        self.vid = vid;
+        self
+    }
+
+    /// Runs the download, returning the contents of the file.
+    /// 
+    /// Returns [crate::errors::DecodeError::InvalidValue] if no name was set.
+    pub fn run(self) -> Result<Vec<u8>, crate::errors::DecodeError> {
+        let name = self.name.ok_or_else(|| crate::errors::DecodeError::InvalidValue("no file name was given for the download".to_string()))?;
+
+        self.device.download_file(name, self.vid)
+    }
+}
+
+/// Guards an initialized file transfer on a [Device].
+/// 
+/// If the guard is dropped without calling [TransferGuard::finish] (for example, because of an error or a panic
+/// partway through a transfer), it sends `FileTransferExit(DoNothing)` so that the brain is not left with an
+/// initialized transfer. Any error from this exit is ignored.
+pub struct TransferGuard<'a, S: Read + Write, U: Read + Write> {
+    device: &'a mut Device<S, U>,
+    response: crate::commands::FileTransferInitResponse,
+    finished: bool,
+}
+
+impl<'a, S: Read + Write, U: Read + Write> TransferGuard<'a, S, U> {
+    /// Returns the response the brain sent when the transfer was initialized
+    pub fn response(&self) -> crate::commands::FileTransferInitResponse {
+        self.response
+    }
+
+    /// Returns the device the transfer is running on
+    pub fn device(&mut self) -> &mut Device<S, U> {
+        self.device
+    }
+
+    /// Writes data to the transfer at the given address
+    pub fn write(&mut self, addr: u32, data: &[u8]) -> Result<(), crate::errors::DecodeError> {
+        self.device.send_request(crate::commands::FileTransferWrite(addr, data))
+    }
+
+    /// Reads data from the transfer at the given address
+    pub fn read(&mut self, addr: u32, nbytes: u16) -> Result<Vec<u8>, crate::errors::DecodeError> {
+        self.device.send_request(crate::commands::FileTransferRead(addr, nbytes))
+    }
+
+    /// Exits the transfer, performing the given action once it is complete
+    pub fn finish(mut self, action: crate::v5::FileTransferComplete) -> Result<(), crate::errors::DecodeError> {
+        // The transfer is exited here, so do not exit it again on drop
+        self.finished = true;
+
+        self.device.send_request(crate::commands::FileTransferExit(action))
+    }
+}
+
+impl<'a, S: Read + Write, U: Read + Write> Drop for TransferGuard<'a, S, U> {
+    fn drop(&mut self) {
+        // Best-effort exit of the transfer, ignoring any errors
+        if !self.finished {
+            let _ = self.device.send_request(crate::commands::FileTransferExit(crate::v5::FileTransferComplete::DoNothing));
+        }
+    }
+}
+
 impl<S, U> std::io::Read for Device<S, U>
 where S: Read + Write, U: Read + Write {
     
@@ -225,10 +1618,7 @@ where S: Read + Write, U: Read + Write {
             p.read(buf)
         } else {
             // If not, then delegate to the read_serial
-            match self.read_serial(buf) {
-                Ok(v) => Ok(v),
-                Err(e) => Err(std::io::Error::new(std::io::ErrorKind::Other, e))
-            }
+            Ok(self.read_serial(buf)?)
         }
     }
 }
@@ -239,7 +1629,7 @@ where S: Read + Write, U: Read + Write {
         if let Some(p) = &mut self.user_port {
             p.write(buf)
         } else {
-            Err(std::io::Error::new(std::io::ErrorKind::Other, crate::errors::DeviceError::NoWriteOnWireless))
+            Err(crate::errors::DeviceError::NoWriteOnWireless.into())
         }
     }
 
@@ -247,7 +1637,264 @@ where S: Read + Write, U: Read + Write {
         if let Some(p) = &mut self.user_port {
             p.flush()
         } else {
-            Err(std::io::Error::new(std::io::ErrorKind::Other, crate::errors::DeviceError::NoWriteOnWireless))
+            Err(crate::errors::DeviceError::NoWriteOnWireless.into())
+        }
+    }
+}
+
+// Compile-time check that a serial Device is Send
+const _: fn() = || {
+    fn assert_send<T: Send>() {}
+
+    assert_send::<Device<Box<dyn tokio_serial::SerialPort>, Box<dyn tokio_serial::SerialPort>>>();
+};
+
+#[cfg(test)]
+mod tests {
+    use super::Device;
+    use crate::commands::{AnyCommand, AnyResponse, Command, GetDirectoryCount, GetSystemFlags};
+    use crate::devices::replay::FixedStream;
+    use crate::errors::DecodeError;
+
+    #[test]
+    fn pipeline_maps_port_timeouts_to_header_timeouts() {
+        let mut device = Device::<_, FixedStream>::new(FixedStream::new(Vec::new()), None);
+
+        let commands = [AnyCommand::GetSystemFlags(GetSystemFlags()), AnyCommand::GetSystemFlags(GetSystemFlags())];
+        let results = device.pipeline(&commands, std::time::Duration::from_millis(50));
+
+        assert!(results.iter().all(|r| matches!(r, Err(DecodeError::HeaderTimeout))));
+    }
+
+    #[test]
+    fn pipeline_matches_responses_to_different_commands() {
+        // The responses arrive in the opposite order to the commands
+        let mut reads = response(0x16, &[0x03, 0x00]);
+        reads.extend(response(0x20, &[0x00, 0x00, 0x00, 0x00, 0xa5, 0x80, 0x01]));
+        let mut device = Device::<_, FixedStream>::new(FixedStream::new(reads), None);
+
+        let commands = [
+            AnyCommand::GetSystemFlags(GetSystemFlags()),
+            AnyCommand::GetDirectoryCount(GetDirectoryCount(crate::v5::FileTransferVID::User, crate::v5::FileTransferOptions::NONE)),
+        ];
+        let results = device.pipeline(&commands, std::time::Duration::from_millis(50));
+
+        assert!(matches!(&results[0], Ok(AnyResponse::GetSystemFlags(flags)) if flags.running_program() == Some(1)));
+        assert!(matches!(results[1], Ok(AnyResponse::GetDirectoryCount(3))));
+    }
+
+    #[test]
+    fn send_request_skips_packets_for_other_commands() {
+        let port = FixedStream::new(vec![
+            // A late simple response
+            0xaa, 0x55, 0xa4, 0x00,
+            // A late NACK to a KVRead
+            0xaa, 0x55, 0x56, 0x04, 0x2e, 0xff, 0xe4, 0x34,
+            // The response to GetSystemFlags, with program 1 running
+            0xaa, 0x55, 0x56, 0x0b, 0x20, 0x76, 0x00, 0x00, 0x00, 0x00, 0xa5, 0x80, 0x01, 0x2c, 0x93,
+        ]);
+        let mut device = Device::<_, FixedStream>::new(port, None);
+
+        let flags = device.send_request(GetSystemFlags()).unwrap();
+
+        assert_eq!(flags.running_program(), Some(1));
+    }
+
+    /// Builds an extended response with an ACK to the extended command `id`, carrying `data`
+    fn response(id: u8, data: &[u8]) -> Vec<u8> {
+        // The extended command id, the ACK, the data, and the CRC
+        let length = data.len() + 4;
+        let mut packet = vec![0xaa, 0x55, 0x56];
+
+        // Lengths of 0x80 and up take two bytes, with the top bit of the first byte set
+        if length >= 0x80 {
+            packet.extend([0x80 | (length >> 8) as u8, length as u8]);
+        } else {
+            packet.push(length as u8);
+        }
+
+        packet.extend([id, 0x76]);
+        packet.extend(data);
+        let crc = crc::Crc::<u16>::new(&crate::VEX_CRC16).checksum(&packet);
+        packet.extend(crc.to_be_bytes());
+
+        packet
+    }
+
+    /// Builds an extended response to a file transfer read whose declared length is `length`
+    fn extended_response(length: usize) -> Vec<u8> {
+        response(0x14, &vec![0xab; length - 4])
+    }
+
+    #[test]
+    fn receive_packet_decodes_lengths_around_0x80() {
+        for length in [127, 128, 129] {
+            let mut device = Device::<_, FixedStream>::new(FixedStream::new(extended_response(length)), None);
+
+            let (command, payload) = device.receive_packet(std::time::Duration::from_secs(1)).unwrap();
+            assert_eq!(payload.len(), length);
+            assert!(device.system_port_mut().is_finished());
+
+            let response = crate::commands::Extended::decode_response(command, payload).unwrap();
+            assert_eq!(response.0, 0x14);
+            assert_eq!(response.1, vec![0xab; length - 4]);
+        }
+    }
+
+    #[test]
+    fn wait_until_ready_gives_up_after_timeout() {
+        let mut device = Device::<_, FixedStream>::new(FixedStream::new(Vec::new()), None);
+
+        let start = std::time::Instant::now();
+        let result = device.wait_until_ready(std::time::Duration::from_millis(300));
+
+        assert!(matches!(result, Err(DecodeError::HeaderTimeout)));
+        assert!(start.elapsed() < std::time::Duration::from_secs(2));
+    }
+
+    /// A port that fails like an unplugged cable once its responses run out
+    struct UnpluggedStream(FixedStream);
+
+    impl std::io::Read for UnpluggedStream {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if self.0.is_finished() {
+                return Err(std::io::ErrorKind::BrokenPipe.into());
+            }
+
+            self.0.read(buf)
         }
     }
-}
\ No newline at end of file
+
+    impl std::io::Write for UnpluggedStream {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            self.0.flush()
+        }
+    }
+
+    /// The responses to verify_all_files for a single file named `a.bin` holding `data`, whose metadata reports `crc`.
+    /// When `complete` is false, the responses stop before the file's data is read.
+    fn verify_responses(data: &[u8], crc: u32, complete: bool) -> Vec<u8> {
+        let mut metadata = vec![0x00];
+        metadata.extend((data.len() as u32).to_le_bytes());
+        metadata.extend(0x0780_0000u32.to_le_bytes());
+        metadata.extend(crc.to_le_bytes());
+        metadata.extend(*b"bin\0");
+        metadata.extend([0; 8]);
+        metadata.extend(crate::commands::encode_file_name("a.bin").unwrap());
+
+        let mut init = 0x1000u16.to_le_bytes().to_vec();
+        init.extend((data.len() as u32).to_le_bytes());
+        init.extend(crc.to_le_bytes());
+
+        let mut responses = response(0x16, &[0x01, 0x00]);
+        responses.extend(response(0x17, &metadata));
+        // GetSystemVersion, from a brain
+        responses.extend([0xaa, 0x55, 0xa4, 0x07, 0x01, 0x02, 0x03, 0x04, 0x00, 0x10, 0x00]);
+        responses.extend(response(0x11, &init));
+
+        if complete {
+            responses.extend(response(0x14, data));
+            responses.extend(response(0x12, &[]));
+        }
+
+        responses
+    }
+
+    #[test]
+    fn verify_all_files_reports_crc_mismatches() {
+        let data = [0x01, 0x02, 0x03, 0x04];
+        let crc = crc::Crc::<u32>::new(&crate::VEX_CRC32).checksum(&data);
+
+        let mut device = Device::<_, FixedStream>::new(FixedStream::new(verify_responses(&data, crc, true)), None);
+        assert_eq!(device.verify_all_files(crate::v5::FileTransferVID::User).unwrap(), vec![("a.bin".to_string(), true)]);
+
+        let mut device = Device::<_, FixedStream>::new(FixedStream::new(verify_responses(&data, crc ^ 1, true)), None);
+        assert_eq!(device.verify_all_files(crate::v5::FileTransferVID::User).unwrap(), vec![("a.bin".to_string(), false)]);
+    }
+
+    #[test]
+    fn verify_all_files_returns_transport_errors() {
+        let data = [0x01, 0x02, 0x03, 0x04];
+        let port = UnpluggedStream(FixedStream::new(verify_responses(&data, 0, false)));
+        let mut device = Device::<_, FixedStream>::new(port, None);
+
+        assert!(matches!(device.verify_all_files(crate::v5::FileTransferVID::User), Err(DecodeError::IoError(_))));
+    }
+
+    #[test]
+    fn raw_file_metadata_rejects_directories_too_large_to_index() {
+        // A directory count of 300
+        let port = FixedStream::new(response(0x16, &[0x2c, 0x01]));
+        let mut device = Device::<_, FixedStream>::new(port, None);
+
+        assert!(matches!(device.raw_file_metadata(crate::v5::FileTransferVID::User), Err(DecodeError::InvalidValue(_))));
+    }
+
+    #[test]
+    fn file_iter_stops_with_an_error_past_the_last_indexable_file() {
+        // A directory count of 300
+        let port = FixedStream::new(response(0x16, &[0x2c, 0x01]));
+        let mut device = Device::<_, FixedStream>::new(port, None);
+
+        let mut files = device.file_iter(crate::v5::FileTransferVID::User).unwrap();
+        assert_eq!(files.total(), 300);
+
+        // Skip the files that can be read by index
+        files.index = 256;
+        assert!(matches!(files.next(), Some(Err(DecodeError::InvalidValue(_)))));
+        assert!(files.next().is_none());
+    }
+
+    #[test]
+    fn display_info_is_only_known_for_brains() {
+        let mut device = Device::<_, FixedStream>::new(FixedStream::new(Vec::new()), None);
+        assert!(matches!(device.display_info(), Err(DecodeError::DeviceError(crate::errors::DeviceError::UnknownDeviceType))));
+
+        device.set_device_type(crate::devices::VexDeviceType::Controller);
+        assert!(matches!(device.display_info(), Err(DecodeError::DeviceError(crate::errors::DeviceError::InvalidDevice))));
+
+        device.set_device_type(crate::devices::VexDeviceType::Brain);
+        assert_eq!(device.display_info().unwrap(), crate::v5::DisplayInfo::V5_BRAIN);
+    }
+
+    /// A command that the device does not respond to
+    #[derive(Clone, Copy)]
+    struct Unanswered;
+
+    impl Command for Unanswered {
+        type Response = ();
+
+        fn encode_request(self) -> Result<(u8, Vec<u8>), DecodeError> {
+            Ok((0x22, vec![0x01]))
+        }
+
+        fn decode_response(command_id: u8, data: Vec<u8>) -> Result<Self::Response, DecodeError> {
+            if command_id != 0x22 {
+                return Err(DecodeError::ExpectedCommand(0x22, command_id));
+            }
+            if !data.is_empty() {
+                return Err(DecodeError::PacketLengthError);
+            }
+            Ok(())
+        }
+
+        fn expects_response(&self) -> bool {
+            false
+        }
+    }
+
+    #[test]
+    fn send_request_does_not_read_when_no_response_is_expected() {
+        // A stray packet that must be left unread
+        let mut device = Device::<_, FixedStream>::new(FixedStream::new(vec![0xaa, 0x55, 0xa4, 0x00]), None);
+
+        device.send_request(Unanswered).unwrap();
+
+        assert!(!device.system_port_mut().is_finished());
+        assert_eq!(device.system_port_mut().written(), [0xc9, 0x36, 0xb8, 0x47, 0x22, 0x01]);
+    }
+}
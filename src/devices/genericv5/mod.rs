@@ -1,4 +1,4 @@
-//! Implements discovering, opening, and interacting with vex devices connected over USB. This module does not have async support.
+//! Implements discovering, opening, and interacting with vex devices connected over USB or Bluetooth serial. This module does not have async support.
 
 
 
@@ -13,8 +13,42 @@ pub struct VexGenericSerialPort {
     pub port_type: VexPortType,
 }
 
+impl VexGenericSerialPort {
+    /// Returns the USB serial number of the port, if it has one
+    pub fn serial_number(&self) -> Option<String> {
+        match &self.port_info.port_type {
+            tokio_serial::SerialPortType::UsbPort(info) => info.serial_number.clone(),
+            _ => None,
+        }
+    }
+}
+
+
+/// Returns true if a port that is not USB looks like a V5 paired over Bluetooth serial (SPP).
+/// 
+/// Ports reported as Bluetooth ports only match if they are named like a V5 (containing `vex` or `v5`), as macOS names them
+/// after the device, such as `/dev/cu.VEX_V5`. Other Bluetooth devices, such as headsets and phones, also have serial ports,
+/// so a Bluetooth port with any other name (including the `COMn` names that Windows uses) is ignored.
+/// Linux names these ports `rfcommN` and does not report them as Bluetooth ports, so ports of an unknown type match by that name too.
+/// The port is not opened, so a Bluetooth port can not be checked any further than this.
+fn is_bluetooth_v5_port(port: &tokio_serial::SerialPortInfo) -> bool {
+    // Only look at the file name of the port, not the directory it is in
+    let name = port.port_name.rsplit('/').next().unwrap_or(&port.port_name).to_lowercase();
+    let named_like_v5 = name.contains("vex") || name.contains("v5");
+
+    match port.port_type {
+        tokio_serial::SerialPortType::BluetoothPort => named_like_v5,
+        tokio_serial::SerialPortType::Unknown => named_like_v5 || is_rfcomm_name(&name),
+        _ => false,
+    }
+}
+
+/// Returns true if a port's file name is `rfcomm` followed by the number of the binding, such as `rfcomm0`
+fn is_rfcomm_name(name: &str) -> bool {
+    name.strip_prefix("rfcomm").is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+}
 
-/// Finds all generic vex v5 ports connected to the computer over usb.
+/// Finds all generic vex v5 ports connected to the computer over usb, along with any Bluetooth serial ports that may be a paired V5.
 fn find_generic_ports() -> Result<Vec<VexGenericSerialPort>, crate::errors::DeviceError> {
 
     // Get all available serial ports
@@ -27,10 +61,15 @@ fn find_generic_ports() -> Result<Vec<VexGenericSerialPort>, crate::errors::Devi
     for port in ports {
 
         // Get the serial port's info as long as it is a usb port.
-        // If it is not a USB port, ignore it.
+        // Bluetooth serial ports that look like a paired V5 are kept, and any other port is ignored.
         let port_info = match port.clone().port_type {
             tokio_serial::SerialPortType::UsbPort(info) => info,
-            _ => continue, // Skip the port if it is not USB.
+            _ => {
+                if is_bluetooth_v5_port(&port) {
+                    vex_ports.push(VexGenericSerialPort { port_info: port, port_type: VexPortType::Bluetooth });
+                }
+                continue;
+            }
         };
 
         // If the Vendor ID does not match the VEX Vendor ID, then skip it
@@ -87,6 +126,9 @@ fn find_generic_ports() -> Result<Vec<VexGenericSerialPort>, crate::errors::Devi
 
 
 /// Finds all generic V5 devices from their ports
+/// 
+/// Ports are classified from their USB descriptors and names alone. No port is opened, so an unresponsive device can not stall discovery,
+/// and [super::SERIAL_TIMEOUT_SECONDS] only applies once a device is opened.
 pub fn find_generic_devices() -> Result<Vec<VexDevice>, crate::errors::DeviceError> {
     // Find all vex ports
     let ports = find_generic_ports()?;
@@ -110,22 +152,33 @@ pub fn find_generic_devices() -> Result<Vec<VexDevice>, crate::errors::DeviceErr
                 vex_devices.push(VexDevice {
                     system_port: current_port.port_info.port_name.clone(),
                     user_port: Some(port_iter.next().unwrap().port_info.port_name.clone()),
-                    device_type: VexDeviceType::Brain
+                    device_type: VexDeviceType::Brain,
+                    serial_number: current_port.serial_number(),
                 });
             } else {
                 // If there is only a system device, add a unknown V5 device
                 vex_devices.push(VexDevice {
                     system_port: current_port.port_info.port_name.clone(),
                     user_port: None,
-                    device_type: VexDeviceType::Unknown
+                    device_type: VexDeviceType::Unknown,
+                    serial_number: current_port.serial_number(),
                 });
             }
+        } else if current_port.port_type == VexPortType::Bluetooth {
+            // A Bluetooth serial port only carries the system channel, and it is not known whether a brain or a controller is on the other end
+            vex_devices.push(VexDevice {
+                system_port: current_port.port_info.port_name.clone(),
+                user_port: None,
+                device_type: VexDeviceType::Unknown,
+                serial_number: None,
+            });
         } else if current_port.port_type == VexPortType::Controller {
             // If it is a controller port, then add a controller device, because controllers have only a single port
             vex_devices.push(VexDevice {
                 system_port: current_port.port_info.port_name.clone(),
                 user_port: None,
-                device_type: VexDeviceType::Controller
+                device_type: VexDeviceType::Controller,
+                serial_number: current_port.serial_number(),
             });
         } else if current_port.port_type == VexPortType::User {
             // If it is a user port, do the same thing we do with a system port. Except ignore it if there is no other port.
@@ -136,7 +189,8 @@ pub fn find_generic_devices() -> Result<Vec<VexDevice>, crate::errors::DeviceErr
                 vex_devices.push(VexDevice {
                     system_port: port_iter.next().unwrap().port_info.port_name.clone(),
                     user_port: Some(current_port.port_info.port_name.clone()),
-                    device_type: VexDeviceType::Brain
+                    device_type: VexDeviceType::Brain,
+                    serial_number: current_port.serial_number(),
                 });
             }
         }
@@ -149,3 +203,40 @@ pub fn find_generic_devices() -> Result<Vec<VexDevice>, crate::errors::DeviceErr
     Ok(vex_devices)
 }
 
+/// Finds a single generic V5 device by its port name (either the system or user port) or its USB serial number.
+/// 
+/// This is useful when several devices of the same type are connected, such as two controllers,
+/// since [find_generic_devices] returns every device without preferring any of them.
+/// Returns None if no connected device matches.
+pub fn find_generic_device(port_or_serial: &str) -> Result<Option<VexDevice>, crate::errors::DeviceError> {
+    Ok(find_generic_devices()?.into_iter().find(|device| {
+        device.system_port == port_or_serial
+            || device.user_port.as_deref() == Some(port_or_serial)
+            || device.serial_number.as_deref() == Some(port_or_serial)
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio_serial::{SerialPortInfo, SerialPortType};
+
+    fn port(name: &str, port_type: SerialPortType) -> SerialPortInfo {
+        SerialPortInfo { port_name: name.to_string(), port_type }
+    }
+
+    #[test]
+    fn bluetooth_ports_must_be_named_like_a_v5() {
+        assert!(super::is_bluetooth_v5_port(&port("/dev/cu.VEX_V5", SerialPortType::BluetoothPort)));
+        assert!(!super::is_bluetooth_v5_port(&port("/dev/cu.Headphones", SerialPortType::BluetoothPort)));
+        assert!(!super::is_bluetooth_v5_port(&port("COM5", SerialPortType::BluetoothPort)));
+    }
+
+    #[test]
+    fn rfcomm_ports_match_by_name() {
+        assert!(super::is_bluetooth_v5_port(&port("/dev/rfcomm0", SerialPortType::Unknown)));
+        assert!(super::is_bluetooth_v5_port(&port("/dev/rfcomm12", SerialPortType::Unknown)));
+        assert!(!super::is_bluetooth_v5_port(&port("/dev/rfcomm", SerialPortType::Unknown)));
+        assert!(!super::is_bluetooth_v5_port(&port("/dev/ttyS0", SerialPortType::Unknown)));
+        assert!(!super::is_bluetooth_v5_port(&port("/dev/rfcomm0", SerialPortType::PciPort)));
+    }
+}
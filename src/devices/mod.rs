@@ -4,6 +4,8 @@ pub mod genericv5;
 pub mod bluetoothv5;
 pub mod device;
 pub mod asyncdevice;
+pub mod replay;
+pub mod reconnecting;
 
 
 /// The default timeout for a serial connection in seconds
@@ -12,6 +14,114 @@ pub const SERIAL_TIMEOUT_SECONDS: u64 = 30;
 /// The default timeout for a serial connection in nanoseconds
 pub const SERIAL_TIMEOUT_NS: u32 = 0;
 
+/// How long each read from a system port opened with [VexDevice::open] waits for data, in milliseconds.
+/// 
+/// Reads that time out are retried until the timeout of the packet being recieved, so this only limits how late that timeout is noticed.
+pub const SERIAL_POLL_TIMEOUT_MS: u64 = 100;
+
+/// Statistics about the traffic sent over a device's system port, collected once enabled with `set_stats_enabled`
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct TransportStats {
+    /// The number of commands sent
+    pub commands_sent: u64,
+    /// The number of bytes sent, including packet headers and CRCs
+    pub bytes_tx: u64,
+    /// The number of bytes recieved in packets, including packet headers and CRCs
+    pub bytes_rx: u64,
+    /// The total time spent in `send_request`, from sending each command until its response was decoded
+    pub total_time: std::time::Duration,
+}
+
+/// The direction of a packet passed to a device's packet tracer
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PacketDirection {
+    /// A packet sent to the device
+    Sent,
+    /// A packet recieved from the device
+    Received,
+}
+
+/// The framing of a packet recieved from a device
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ResponseFraming {
+    /// A simple packet, with a one byte length and no CRC, such as the response to [crate::commands::GetSystemVersion]
+    Simple,
+    /// An extended packet (simple command id 0x56), with an extended command id, a one or two byte length, an ACK, and a CRC16
+    Extended,
+}
+
+impl ResponseFraming {
+    /// Returns the framing of a packet with the given simple command id
+    /// 
+    /// # Examples
+    /// 
+    /// ```rust
+    /// use vexv5_serial::devices::ResponseFraming;
+    /// 
+    /// assert_eq!(ResponseFraming::from_command_id(0xA4), ResponseFraming::Simple);
+    /// assert_eq!(ResponseFraming::from_command_id(0x56), ResponseFraming::Extended);
+    /// ```
+    pub fn from_command_id(command_id: u8) -> Self {
+        match command_id {
+            0x56 => ResponseFraming::Extended,
+            _ => ResponseFraming::Simple,
+        }
+    }
+}
+
+/// A hook called with every full packet sent or recieved on a device's system port
+pub(crate) type PacketTracer = Box<dyn Fn(PacketDirection, &[u8]) + Send>;
+
+/// A hook called with the raw ACK byte and decoded type of each extended response
+pub(crate) type AckObserver = Box<dyn Fn(u8, crate::errors::VexACKType) + Send>;
+
+/// Returns the name of a program's file in a slot (1-8), which is `slot_N.<extension>`
+pub(crate) fn slot_file_name(slot: u8, extension: &str) -> Result<String, crate::errors::DecodeError> {
+    // There are only eight program slots
+    if !(1..=8).contains(&slot) {
+        return Err(crate::errors::DecodeError::InvalidValue(format!("slot {slot} is not between 1 and 8")));
+    }
+
+    Ok(format!("slot_{slot}.{extension}"))
+}
+
+/// Generates the ini file that describes the program in a slot (1-8), in the format VEXcode and PROS-CLI use
+pub(crate) fn program_ini(slot: u8, name: &str, description: &str) -> Result<String, crate::errors::DecodeError> {
+    // A new line would start a new ini entry
+    if name.contains(['\r', '\n']) || description.contains(['\r', '\n']) {
+        return Err(crate::errors::DecodeError::InvalidValue("program name and description must be a single line".to_string()));
+    }
+
+    // The slot in the ini file is zero-indexed
+    Ok(format!(
+        "[project]\nversion = {}\nide = vexv5_serial\n\n[program]\nversion = 0.0.0\nname = {}\nslot = {}\nicon = USER902x.bmp\ndescription = {}\n",
+        env!("CARGO_PKG_VERSION"), name, slot - 1, description
+    ))
+}
+
+/// Identifies the command an encoded request is for.
+/// 
+/// Returns the simple command id, along with the extended command id if it is an extended packet.
+pub(crate) fn request_command_id(encoded: &(u8, Vec<u8>)) -> (u8, Option<u8>) {
+    if encoded.0 == 0x56 {
+        // Encoded extended packets include the header, so the extended command id is at index 5
+        (encoded.0, encoded.1.get(5).copied())
+    } else {
+        (encoded.0, None)
+    }
+}
+
+/// Identifies the command a recieved packet is a response to.
+/// 
+/// Returns the simple command id, along with the extended command id if it is an extended packet.
+pub(crate) fn response_command_id(command: u8, payload: &[u8]) -> (u8, Option<u8>) {
+    if command == 0x56 {
+        (command, payload.first().copied())
+    } else {
+        (command, None)
+    }
+}
+
 /// The USB PID of the V5 Brain
 const VEX_V5_BRAIN_USB_PID: u16 = 0x0501;
 
@@ -21,15 +131,17 @@ const VEX_V5_CONTROLLER_USB_PID: u16 = 0x0503;
 /// The USB VID for Vex devices
 const VEX_USB_VID: u16 = 0x2888;
 
-/// This enum represents three types of Vex serial devices:
+/// This enum represents four types of Vex serial devices:
 /// The User port for communication with the user program.
 /// The System port for communicating with VexOS.
-/// And the Controller port for communicating with the VexV5 joystick
+/// The Controller port for communicating with the VexV5 joystick.
+/// And the Bluetooth port, a Bluetooth serial (SPP) port that a paired V5 may be exposed as, such as `/dev/rfcomm0` on Linux.
 #[derive(PartialEq, Debug, Clone)]
 pub enum VexPortType {
     User,
     System,
     Controller,
+    Bluetooth,
 }
 
 /// The type of a vex device
@@ -50,7 +162,48 @@ pub struct VexDevice {
     pub user_port: Option<String>,
     
     /// The type of the device
-    pub device_type: VexDeviceType
+    pub device_type: VexDeviceType,
+
+    /// The USB serial number of the device, if it reported one.
+    /// This can be used to tell devices of the same type apart, such as two controllers.
+    pub serial_number: Option<String>,
+}
+
+impl std::fmt::Display for VexDeviceType {
+    /// Formats the type as a name to show to users, such as `V5 Brain`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VexDeviceType::Brain => write!(f, "V5 Brain"),
+            VexDeviceType::Controller => write!(f, "V5 Controller"),
+            VexDeviceType::Unknown => write!(f, "Unknown V5 Device"),
+        }
+    }
+}
+
+impl std::fmt::Display for VexDevice {
+    /// Formats the device as its type followed by its ports
+    /// 
+    /// # Example
+    /// ```rust
+    /// use vexv5_serial::devices::{VexDevice, VexDeviceType};
+    /// 
+    /// let device = VexDevice {
+    ///     system_port: "COM3".to_string(),
+    ///     user_port: Some("COM4".to_string()),
+    ///     device_type: VexDeviceType::Brain,
+    ///     serial_number: None,
+    /// };
+    /// assert_eq!(device.to_string(), "V5 Brain (system: COM3, user: COM4)");
+    /// ```
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (system: {}", self.device_type, self.system_port)?;
+
+        if let Some(user_port) = &self.user_port {
+            write!(f, ", user: {}", user_port)?;
+        }
+
+        write!(f, ")")
+    }
 }
 
 /// A basic no-async vex serial port.
@@ -58,11 +211,24 @@ type VexSerialPort = Box<dyn tokio_serial::SerialPort>;
 
 impl VexDevice {
     /// Open the device
+    /// 
+    /// If the user port fails to open (for example, because another process has claimed it), the device is still opened
+    /// without a user port, so that commands can be sent over the system port. Use [VexDevice::open_strict] to fail instead.
     pub fn open(&self) -> Result<device::Device<VexSerialPort, VexSerialPort>, crate::errors::DeviceError> {
+        self.open_with_strictness(false)
+    }
+
+    /// Open the device, failing if either the system port or the user port can not be opened
+    pub fn open_strict(&self) -> Result<device::Device<VexSerialPort, VexSerialPort>, crate::errors::DeviceError> {
+        self.open_with_strictness(true)
+    }
+
+    /// Open the device, only failing if the user port can not be opened when `strict` is true
+    fn open_with_strictness(&self, strict: bool) -> Result<device::Device<VexSerialPort, VexSerialPort>, crate::errors::DeviceError> {
         // Open the system port
         let system_port = match tokio_serial::new(&self.system_port, 115200)
             .parity(tokio_serial::Parity::None)
-            .timeout(std::time::Duration::new(crate::devices::SERIAL_TIMEOUT_SECONDS, crate::devices::SERIAL_TIMEOUT_NS))
+            .timeout(std::time::Duration::from_millis(crate::devices::SERIAL_POLL_TIMEOUT_MS))
             .stop_bits(tokio_serial::StopBits::One).open() {
                 Ok(v) => Ok(v),
                 Err(e) => Err(crate::errors::DeviceError::SerialportError(e)),
@@ -71,31 +237,47 @@ impl VexDevice {
         // Open the user port (if it exists)
         
         let user_port = if let Some(port) = &self.user_port {
-            Some(match tokio_serial::new(port, 115200)
+            match tokio_serial::new(port, 115200)
                 .parity(tokio_serial::Parity::None)
                 .timeout(std::time::Duration::new(crate::devices::SERIAL_TIMEOUT_SECONDS, crate::devices::SERIAL_TIMEOUT_NS))
                 .stop_bits(tokio_serial::StopBits::One).open()
                 {
-                Ok(v) => Ok(v),
-                Err(e) => Err(crate::errors::DeviceError::SerialportError(e)),
-            }?)
+                Ok(v) => Some(v),
+                Err(e) if strict => return Err(crate::errors::DeviceError::SerialportError(e)),
+                // If we are not strict, continue without the user port
+                Err(_) => None,
+            }
         } else {
             None
         };
         
 
         // Create the device
-        let dev = device::Device::new(
+        let mut dev = device::Device::new(
             system_port,
             user_port,
         );
+        dev.set_device_type(self.device_type.clone());
 
         // Return the device
         Ok(dev)
     }
 
     /// Open the device with async support
+    /// 
+    /// If the user port fails to open (for example, because another process has claimed it), the device is still opened
+    /// without a user port, so that commands can be sent over the system port. Use [VexDevice::open_async_strict] to fail instead.
     pub fn open_async(&self) -> Result<asyncdevice::AsyncDevice<tokio_serial::SerialStream, tokio_serial::SerialStream>, crate::errors::DeviceError> {
+        self.open_async_with_strictness(false)
+    }
+
+    /// Open the device with async support, failing if either the system port or the user port can not be opened
+    pub fn open_async_strict(&self) -> Result<asyncdevice::AsyncDevice<tokio_serial::SerialStream, tokio_serial::SerialStream>, crate::errors::DeviceError> {
+        self.open_async_with_strictness(true)
+    }
+
+    /// Open the device with async support, only failing if the user port can not be opened when `strict` is true
+    fn open_async_with_strictness(&self, strict: bool) -> Result<asyncdevice::AsyncDevice<tokio_serial::SerialStream, tokio_serial::SerialStream>, crate::errors::DeviceError> {
         // Open the system port
         let system_port = match tokio_serial::SerialStream::open(&tokio_serial::new(&self.system_port, 115200)
             .parity(tokio_serial::Parity::None)
@@ -108,27 +290,102 @@ impl VexDevice {
         // Open the user port (if it exists)
         
         let user_port = if let Some(port) = &self.user_port {
-            Some(match tokio_serial::SerialStream::open(&tokio_serial::new(port, 115200)
+            match tokio_serial::SerialStream::open(&tokio_serial::new(port, 115200)
                 .parity(tokio_serial::Parity::None)
                 .timeout(std::time::Duration::new(crate::devices::SERIAL_TIMEOUT_SECONDS, crate::devices::SERIAL_TIMEOUT_NS))
                 .stop_bits(tokio_serial::StopBits::One))
                 {
-                Ok(v) => Ok(v),
-                Err(e) => Err(crate::errors::DeviceError::SerialportError(e)),
-            }?)
+                Ok(v) => Some(v),
+                Err(e) if strict => return Err(crate::errors::DeviceError::SerialportError(e)),
+                // If we are not strict, continue without the user port
+                Err(_) => None,
+            }
         } else {
             None
         };
         
 
         // Create the device
-        let dev = asyncdevice::AsyncDevice::new(
+        let mut dev = asyncdevice::AsyncDevice::new(
             system_port,
             user_port,
         );
+        dev.set_device_type(self.device_type.clone());
 
         // Return the device
         Ok(dev)
     }
     
 }
+
+/// Encodes every packet that `upload_file` on a device would send to upload a file, without sending any of them.
+/// 
+/// This validates the upload (the name, the file size, and the size and padding of every write) without a connected brain,
+/// and returns what would have been sent as [TransportStats]. The brain decides the size of each write when the transfer is
+/// initialized, so `max_packet_size` stands in for the value it would respond with. Only the timestamp differs from a real upload.
+/// 
+/// # Examples
+/// 
+/// ```rust
+/// use vexv5_serial::devices::dry_run_upload;
+/// use vexv5_serial::v5::{FileTransferVID, FileTransferType, FileTransferComplete};
+/// 
+/// let stats = dry_run_upload("slot_1.bin", FileTransferVID::User, FileTransferType::Bin, &[0; 1000], FileTransferComplete::RunProgram, 512)?;
+/// 
+/// // The init, two writes, and the exit
+/// assert_eq!(stats.commands_sent, 4);
+/// 
+/// // Names longer than 23 characters can not be uploaded
+/// assert!(dry_run_upload("a_name_that_is_far_too_long.bin", FileTransferVID::User, FileTransferType::Bin, &[], FileTransferComplete::DoNothing, 512).is_err());
+/// # Ok::<(), vexv5_serial::errors::DecodeError>(())
+/// ```
+pub fn dry_run_upload(name: &str, vid: crate::v5::FileTransferVID, file_type: crate::v5::FileTransferType, data: &[u8], on_complete: crate::v5::FileTransferComplete, max_packet_size: u16) -> Result<TransportStats, crate::errors::DecodeError> {
+    use crate::commands::Command;
+
+    let mut stats = TransportStats::default();
+    let mut record = |encoded: (u8, Vec<u8>)| {
+        stats.commands_sent += 1;
+        stats.bytes_tx += encoded.1.len() as u64;
+    };
+
+    // The length is sent as a u32
+    let length = u32::try_from(data.len()).map_err(|_| crate::errors::DecodeError::InvalidValue("file is too large to upload".to_string()))?;
+    let crc = crc::Crc::<u32>::new(&crate::VEX_CRC32).checksum(data);
+    let timestamp = crate::v5::system_time_to_v5_timestamp(std::time::SystemTime::now());
+    let init = upload_init(name, vid, file_type, length, crc, timestamp, 0x01000000)?;
+    let addr = init.addr;
+    record(init.encode_request()?);
+
+    // Split the file the same way the upload would
+    let chunk_size = (max_packet_size as usize / 4) * 4;
+    if chunk_size == 0 {
+        return Err(crate::errors::DecodeError::PacketLengthError);
+    }
+
+    for (i, chunk) in data.chunks(chunk_size).enumerate() {
+        record(crate::commands::FileTransferWrite(addr + (i * chunk_size) as u32, chunk).encode_request()?);
+    }
+
+    record(crate::commands::FileTransferExit(on_complete).encode_request()?);
+
+    Ok(stats)
+}
+
+/// Builds the [crate::commands::FileTransferInit] for uploading a file to the brain's flash, overwriting any file with the same name
+pub(crate) fn upload_init(name: &str, vid: crate::v5::FileTransferVID, file_type: crate::v5::FileTransferType, length: u32, crc: u32, timestamp: u32, version: u32) -> Result<crate::commands::FileTransferInit, crate::errors::DecodeError> {
+    let target = crate::v5::FileTransferTarget::Flash;
+
+    Ok(crate::commands::FileTransferInit {
+        function: crate::v5::FileTransferFunction::Upload,
+        target,
+        vid,
+        options: crate::v5::FileTransferOptions::OVERWRITE,
+        file_type,
+        length,
+        addr: crate::commands::FileTransferInit::default_addr(target, vid),
+        crc,
+        timestamp,
+        version,
+        name: crate::commands::encode_file_name(name)?,
+    })
+}
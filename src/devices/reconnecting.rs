@@ -0,0 +1,166 @@
+//! Implements device wrappers that reopen their ports when the connection is lost, for long-running sessions.
+
+use super::{VexDevice, VexSerialPort};
+
+/// How a reconnecting device tries to reopen its ports after the connection is lost
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ReconnectPolicy {
+    /// The number of times to try reopening the device before giving up
+    pub attempts: usize,
+    /// How long to wait before each attempt, giving the device time to reappear
+    pub delay: std::time::Duration,
+}
+
+impl Default for ReconnectPolicy {
+    /// Tries three times, one second apart
+    fn default() -> Self {
+        Self {
+            attempts: 3,
+            delay: std::time::Duration::from_secs(1),
+        }
+    }
+}
+
+/// Returns true if an error means that the connection to the device was lost, rather than that a command failed
+fn is_connection_error(err: &crate::errors::DecodeError) -> bool {
+    use crate::errors::{DecodeError, DeviceError};
+
+    matches!(err,
+        DecodeError::IoError(_) |
+        DecodeError::DeviceError(DeviceError::IoError(_)) |
+        DecodeError::DeviceError(DeviceError::SerialportError(_)) |
+        DecodeError::DeviceError(DeviceError::NotConnected)
+    )
+}
+
+/// Finds the device again, preferring its USB serial number, since its port names can change when it is plugged back in
+fn find_again(info: &VexDevice) -> Result<VexDevice, crate::errors::DeviceError> {
+    let key = info.serial_number.as_deref().unwrap_or(&info.system_port);
+
+    super::genericv5::find_generic_device(key)?.ok_or(crate::errors::DeviceError::NotConnected)
+}
+
+/// A [super::device::Device] that reopens its ports when the connection is lost
+///
+/// When a request fails because of an IO error or a closed connection, the device is found again (by its USB serial
+/// number if it has one, or else by its system port), reopened according to the [ReconnectPolicy], and the request is retried once.
+/// Settings made on the inner device (such as the command delay and any hooks) belong to the old connection,
+/// so they have to be made again after a reconnect.
+pub struct ReconnectingDevice {
+    info: VexDevice,
+    device: super::device::Device<VexSerialPort, VexSerialPort>,
+    policy: ReconnectPolicy,
+}
+
+impl ReconnectingDevice {
+    /// Opens the device, reconnecting with the default [ReconnectPolicy]
+    pub fn open(info: VexDevice) -> Result<Self, crate::errors::DeviceError> {
+        Ok(Self {
+            device: info.open()?,
+            info,
+            policy: ReconnectPolicy::default(),
+        })
+    }
+
+    /// Sets how the device reconnects
+    pub fn set_reconnect_policy(&mut self, policy: ReconnectPolicy) {
+        self.policy = policy;
+    }
+
+    /// Returns the device for the current connection. Commands sent through it directly are not retried.
+    pub fn device(&mut self) -> &mut super::device::Device<VexSerialPort, VexSerialPort> {
+        &mut self.device
+    }
+
+    /// Sends a command and recieves its response, reconnecting and retrying once if the connection was lost
+    pub fn send_request<C: crate::commands::Command + Copy>(&mut self, command: C) -> Result<C::Response, crate::errors::DecodeError> {
+        match self.device.send_request(command) {
+            Err(e) if is_connection_error(&e) => {
+                self.reconnect()?;
+                self.device.send_request(command)
+            }
+            result => result,
+        }
+    }
+
+    /// Finds and reopens the device, trying as many times as the [ReconnectPolicy] allows
+    pub fn reconnect(&mut self) -> Result<(), crate::errors::DeviceError> {
+        let mut last_error = crate::errors::DeviceError::NotConnected;
+
+        for _ in 0..self.policy.attempts.max(1) {
+            std::thread::sleep(self.policy.delay);
+
+            match find_again(&self.info).and_then(|info| Ok((info.open()?, info))) {
+                Ok((device, info)) => {
+                    self.device = device;
+                    self.info = info;
+                    return Ok(());
+                }
+                Err(e) => last_error = e,
+            }
+        }
+
+        Err(last_error)
+    }
+}
+
+/// An [super::asyncdevice::AsyncDevice] that reopens its ports when the connection is lost
+///
+/// This works the same way as [ReconnectingDevice].
+pub struct AsyncReconnectingDevice {
+    info: VexDevice,
+    device: super::asyncdevice::AsyncDevice<tokio_serial::SerialStream, tokio_serial::SerialStream>,
+    policy: ReconnectPolicy,
+}
+
+impl AsyncReconnectingDevice {
+    /// Opens the device, reconnecting with the default [ReconnectPolicy]
+    pub fn open(info: VexDevice) -> Result<Self, crate::errors::DeviceError> {
+        Ok(Self {
+            device: info.open_async()?,
+            info,
+            policy: ReconnectPolicy::default(),
+        })
+    }
+
+    /// Sets how the device reconnects
+    pub fn set_reconnect_policy(&mut self, policy: ReconnectPolicy) {
+        self.policy = policy;
+    }
+
+    /// Returns the device for the current connection. Commands sent through it directly are not retried.
+    pub fn device(&mut self) -> &mut super::asyncdevice::AsyncDevice<tokio_serial::SerialStream, tokio_serial::SerialStream> {
+        &mut self.device
+    }
+
+    /// Sends a command and recieves its response, reconnecting and retrying once if the connection was lost
+    pub async fn send_request<C: crate::commands::Command + Copy>(&mut self, command: C) -> Result<C::Response, crate::errors::DecodeError> {
+        match self.device.send_request(command).await {
+            Err(e) if is_connection_error(&e) => {
+                self.reconnect().await?;
+                self.device.send_request(command).await
+            }
+            result => result,
+        }
+    }
+
+    /// Finds and reopens the device, trying as many times as the [ReconnectPolicy] allows
+    pub async fn reconnect(&mut self) -> Result<(), crate::errors::DeviceError> {
+        let mut last_error = crate::errors::DeviceError::NotConnected;
+
+        for _ in 0..self.policy.attempts.max(1) {
+            tokio::time::sleep(self.policy.delay).await;
+
+            match find_again(&self.info).and_then(|info| Ok((info.open_async()?, info))) {
+                Ok((device, info)) => {
+                    self.device = device;
+                    self.info = info;
+                    return Ok(());
+                }
+                Err(e) => last_error = e,
+            }
+        }
+
+        Err(last_error)
+    }
+}
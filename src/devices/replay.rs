@@ -0,0 +1,361 @@
+//! Transport wrappers for recording the traffic with a real device and replaying it later without hardware.
+//!
+//! A [RecordingStream] wraps a port and logs every byte read from and written to it. A [ReplayStream] loads that log
+//! and acts as the port: reads return the recorded bytes, and writes are checked against the recorded writes.
+//! A [FixedStream] is simpler still: it returns a fixed buffer to reads and keeps whatever is written to it, for decoding
+//! hand-written responses without a recording.
+//! Any of these can be passed to [super::device::Device::new] or [super::asyncdevice::AsyncDevice::new] in place of a serial port.
+//!
+//! The log is a sequence of records, each made of a direction byte (0 for read, 1 for write),
+//! the length of the data as a little endian u32, and then the data itself.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use std::io::{Read, Write};
+//! use vexv5_serial::devices::replay::{RecordingStream, ReplayStream};
+//!
+//! let path = std::env::temp_dir().join("vexv5_serial_replay_example.bin");
+//!
+//! // Record a session. In practice the wrapped stream would be a serial port.
+//! let mut recording = RecordingStream::new(std::io::Cursor::new(vec![0xAA, 0x55]), &path)?;
+//! let mut response = [0u8; 2];
+//! recording.read_exact(&mut response)?;
+//! recording.write_all(&[0xc9, 0x36, 0xb8, 0x47, 0xA4])?;
+//! drop(recording);
+//!
+//! // Replay the same session
+//! let mut replay = ReplayStream::open(&path)?;
+//! let mut replayed = [0u8; 2];
+//! replay.read_exact(&mut replayed)?;
+//! replay.write_all(&[0xc9, 0x36, 0xb8, 0x47, 0xA4])?;
+//!
+//! assert_eq!(response, replayed);
+//! assert!(replay.is_finished());
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use std::collections::VecDeque;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+/// The direction byte of a record of data read from the device
+const RECORD_READ: u8 = 0;
+
+/// The direction byte of a record of data written to the device
+const RECORD_WRITE: u8 = 1;
+
+/// Wraps a port, logging every byte read from and written to it to a file
+pub struct RecordingStream<S> {
+    inner: S,
+    log: std::fs::File,
+}
+
+impl<S> RecordingStream<S> {
+    /// Wraps `inner`, creating (or truncating) the log file at `path`
+    pub fn new(inner: S, path: impl AsRef<Path>) -> std::io::Result<Self> {
+        Ok(Self {
+            inner,
+            log: std::fs::File::create(path)?,
+        })
+    }
+
+    /// Returns the wrapped port
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+
+    /// Appends a record to the log
+    fn record(&mut self, direction: u8, data: &[u8]) -> std::io::Result<()> {
+        // Empty reads and writes carry no data, so they are not worth recording
+        if data.is_empty() {
+            return Ok(());
+        }
+
+        let mut record = vec![direction];
+        record.extend((data.len() as u32).to_le_bytes());
+        record.extend(data);
+
+        self.log.write_all(&record)
+    }
+}
+
+impl<S: Read> Read for RecordingStream<S> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.record(RECORD_READ, &buf[..n])?;
+        Ok(n)
+    }
+}
+
+impl<S: Write> Write for RecordingStream<S> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.record(RECORD_WRITE, &buf[..n])?;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()?;
+        self.log.flush()
+    }
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for RecordingStream<S> {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<std::io::Result<()>> {
+        // Only the bytes filled by this read should be recorded
+        let start = buf.filled().len();
+
+        match Pin::new(&mut self.inner).poll_read(cx, buf) {
+            Poll::Ready(Ok(())) => Poll::Ready(self.record(RECORD_READ, &buf.filled()[start..])),
+            other => other,
+        }
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for RecordingStream<S> {
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<std::io::Result<usize>> {
+        match Pin::new(&mut self.inner).poll_write(cx, buf) {
+            Poll::Ready(Ok(n)) => Poll::Ready(self.record(RECORD_WRITE, &buf[..n]).map(|_| n)),
+            other => other,
+        }
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        match Pin::new(&mut self.inner).poll_flush(cx) {
+            Poll::Ready(Ok(())) => Poll::Ready(self.log.flush()),
+            other => other,
+        }
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+/// Replays a log written by [RecordingStream]
+///
+/// Reads return the recorded reads in order. Once they run out, reads fail with [std::io::ErrorKind::TimedOut],
+/// the same way a serial port with no more data would. Writes must match the recorded writes,
+/// and fail with [std::io::ErrorKind::InvalidData] if they do not.
+#[derive(Clone, Debug)]
+pub struct ReplayStream {
+    reads: VecDeque<u8>,
+    writes: VecDeque<u8>,
+}
+
+impl ReplayStream {
+    /// Loads the log at `path`
+    pub fn open(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        Self::from_log(&std::fs::read(path)?)
+    }
+
+    /// Loads a log that has already been read into memory
+    pub fn from_log(mut log: &[u8]) -> std::io::Result<Self> {
+        let mut reads = VecDeque::new();
+        let mut writes = VecDeque::new();
+
+        while !log.is_empty() {
+            // Each record starts with a direction byte and a u32 length
+            if log.len() < 5 {
+                return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "truncated record header"));
+            }
+            let direction = log[0];
+            let length = u32::from_le_bytes(log[1..5].try_into().unwrap()) as usize;
+
+            let data = log.get(5..5 + length).ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidData, "truncated record data"))?;
+
+            match direction {
+                RECORD_READ => reads.extend(data),
+                RECORD_WRITE => writes.extend(data),
+                _ => return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "unknown record direction")),
+            }
+
+            log = &log[5 + length..];
+        }
+
+        Ok(Self { reads, writes })
+    }
+
+    /// Returns true if every recorded read has been returned and every recorded write has been matched
+    pub fn is_finished(&self) -> bool {
+        self.reads.is_empty() && self.writes.is_empty()
+    }
+
+    /// Reads as many recorded bytes as fit in `buf`
+    fn replay_read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.reads.is_empty() && !buf.is_empty() {
+            return Err(std::io::Error::new(std::io::ErrorKind::TimedOut, "no recorded data left to read"));
+        }
+
+        let n = buf.len().min(self.reads.len());
+        for (b, r) in buf.iter_mut().zip(self.reads.drain(..n)) {
+            *b = r;
+        }
+
+        Ok(n)
+    }
+
+    /// Checks `buf` against the next recorded writes
+    fn replay_write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if buf.len() > self.writes.len() || !self.writes.iter().zip(buf).all(|(w, b)| w == b) {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "write does not match the recording"));
+        }
+
+        self.writes.drain(..buf.len());
+
+        Ok(buf.len())
+    }
+}
+
+impl Read for ReplayStream {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.replay_read(buf)
+    }
+}
+
+impl Write for ReplayStream {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.replay_write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl AsyncRead for ReplayStream {
+    fn poll_read(mut self: Pin<&mut Self>, _cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<std::io::Result<()>> {
+        let n = match self.replay_read(buf.initialize_unfilled()) {
+            Ok(n) => n,
+            Err(e) => return Poll::Ready(Err(e)),
+        };
+        buf.advance(n);
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncWrite for ReplayStream {
+    fn poll_write(mut self: Pin<&mut Self>, _cx: &mut Context<'_>, buf: &[u8]) -> Poll<std::io::Result<usize>> {
+        Poll::Ready(self.replay_write(buf))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// A port that returns a fixed buffer to reads, and keeps every byte written to it
+///
+/// A plain [std::io::Cursor] can not be used as a port, because the commands written to it would overwrite the responses in its buffer.
+/// Once the buffer runs out, reads fail with [std::io::ErrorKind::TimedOut], the same way a serial port with no more data would.
+///
+/// # Examples
+///
+/// ```rust
+/// use vexv5_serial::commands::GetSystemFlags;
+/// use vexv5_serial::devices::device::Device;
+/// use vexv5_serial::devices::replay::FixedStream;
+///
+/// // A response to GetSystemFlags with program 1 running
+/// let port = FixedStream::new(vec![
+///     0xaa, 0x55, 0x56, 0x0b, 0x20, 0x76, 0x00, 0x00, 0x00, 0x00, 0xa5, 0x80, 0x01, 0x2c, 0x93,
+/// ]);
+/// let mut device = Device::<_, FixedStream>::new(port, None);
+///
+/// let flags = device.response_for::<GetSystemFlags>(std::time::Duration::from_secs(1))?;
+/// assert_eq!(flags.running_program(), Some(1));
+/// # Ok::<(), vexv5_serial::errors::DecodeError>(())
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct FixedStream {
+    reads: VecDeque<u8>,
+    written: Vec<u8>,
+}
+
+impl FixedStream {
+    /// Creates a port that returns `reads` to reads
+    pub fn new(reads: impl Into<Vec<u8>>) -> Self {
+        Self {
+            reads: reads.into().into(),
+            written: Vec::new(),
+        }
+    }
+
+    /// Returns every byte that has been written to the port
+    pub fn written(&self) -> &[u8] {
+        &self.written
+    }
+
+    /// Returns true if every byte of the buffer has been read
+    pub fn is_finished(&self) -> bool {
+        self.reads.is_empty()
+    }
+
+    /// Reads as many bytes of the buffer as fit in `buf`
+    fn fixed_read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.reads.is_empty() && !buf.is_empty() {
+            return Err(std::io::Error::new(std::io::ErrorKind::TimedOut, "no data left to read"));
+        }
+
+        let n = buf.len().min(self.reads.len());
+        for (b, r) in buf.iter_mut().zip(self.reads.drain(..n)) {
+            *b = r;
+        }
+
+        Ok(n)
+    }
+}
+
+impl Read for FixedStream {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.fixed_read(buf)
+    }
+}
+
+impl Write for FixedStream {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.written.extend(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl AsyncRead for FixedStream {
+    fn poll_read(mut self: Pin<&mut Self>, _cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<std::io::Result<()>> {
+        let n = match self.fixed_read(buf.initialize_unfilled()) {
+            Ok(n) => n,
+            Err(e) => return Poll::Ready(Err(e)),
+        };
+        buf.advance(n);
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncWrite for FixedStream {
+    fn poll_write(mut self: Pin<&mut Self>, _cx: &mut Context<'_>, buf: &[u8]) -> Poll<std::io::Result<usize>> {
+        self.written.extend(buf);
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
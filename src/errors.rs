@@ -23,6 +23,9 @@ pub enum DecodeError {
     /// Raised whenever a packet length does not match the expected length
     #[error("packet length is incorrect")]
     PacketLengthError,
+    /// Raised whenever the length a packet declares does not match the number of bytes recieved, as (declared, recieved)
+    #[error("packet declares a length of {0} bytes, but {1} bytes were recieved")]
+    PacketLengthMismatch(usize, usize),
     /// Raised whenever an invalid ACK number is recieved
     #[error("invalid ack number")]
     InvalidAck,
@@ -64,9 +67,72 @@ pub enum DeviceError {
     /// Raised whenever a user attempts to communicate with an unconnected device
     #[error("Not connected to the device")]
     NotConnected,
+    /// Raised whenever a user attempts to communicate with a bluetooth device before handshaking with it
+    #[error("Not authenticated with the device")]
+    NotAuthenticated,
     /// Raised whenever a bluetooth device returns an invalid magic number
     #[error("Invalid Magic Number")]
-    InvalidMagic
+    InvalidMagic,
+    /// Raised whenever the device does not complete an operation in time
+    #[error("Timed out waiting for the device")]
+    Timeout,
+    /// Raised whenever an operation needs to know the type of a device that was opened without one being found
+    #[error("The type of the device is not known")]
+    UnknownDeviceType,
+    /// Raised whenever a command that would modify a device is sent while the device is in read-only mode
+    #[error("The device is in read-only mode")]
+    ReadOnly,
+}
+
+impl From<DecodeError> for std::io::Error {
+    /// Converts a [DecodeError] into an [std::io::Error], preserving the original error as the source.
+    /// 
+    /// Inner io errors are passed through unchanged, header timeouts map to [std::io::ErrorKind::TimedOut],
+    /// and malformed packets map to [std::io::ErrorKind::InvalidData].
+    fn from(err: DecodeError) -> Self {
+        let kind = match err {
+            // Pass along inner errors so that their kind is kept
+            DecodeError::IoError(e) => return e,
+            DecodeError::DeviceError(e) => return e.into(),
+            DecodeError::HeaderTimeout => std::io::ErrorKind::TimedOut,
+            DecodeError::UTF8Error(_) |
+            DecodeError::ExpectedExtended |
+            DecodeError::CrcError |
+            DecodeError::PacketLengthError |
+            DecodeError::PacketLengthMismatch(_, _) |
+            DecodeError::InvalidAck |
+            DecodeError::ExpectedCommand(_, _) => std::io::ErrorKind::InvalidData,
+            DecodeError::InvalidValue(_) => std::io::ErrorKind::InvalidInput,
+            DecodeError::NACK(_) => std::io::ErrorKind::Other,
+        };
+
+        std::io::Error::new(kind, err)
+    }
+}
+
+impl From<DeviceError> for std::io::Error {
+    /// Converts a [DeviceError] into an [std::io::Error], preserving the original error as the source.
+    /// 
+    /// Inner io and serialport errors are converted directly, and a closed connection maps to
+    /// [std::io::ErrorKind::ConnectionAborted].
+    fn from(err: DeviceError) -> Self {
+        let kind = match err {
+            DeviceError::IoError(e) => return e,
+            DeviceError::SerialportError(e) => return e.into(),
+            DeviceError::NotConnected => std::io::ErrorKind::ConnectionAborted,
+            DeviceError::NotAuthenticated |
+            DeviceError::ReadOnly => std::io::ErrorKind::PermissionDenied,
+            DeviceError::Timeout => std::io::ErrorKind::TimedOut,
+            DeviceError::NoWriteOnWireless |
+            DeviceError::UnknownDeviceType => std::io::ErrorKind::Unsupported,
+            DeviceError::NoBluetoothAdapter => std::io::ErrorKind::NotFound,
+            DeviceError::InvalidDevice |
+            DeviceError::InvalidMagic => std::io::ErrorKind::InvalidData,
+            DeviceError::BluetoothError(_) => std::io::ErrorKind::Other,
+        };
+
+        std::io::Error::new(kind, err)
+    }
 }
 
 /// A V5 device can respond with various different acknowledgements.
@@ -125,6 +191,7 @@ impl VexACKType {
             0xD9 => Ok(Self::NACKDirectoryNoExist),
             0xDA => Ok(Self::NACKNoFileRoom),
             0xDB => Ok(Self::NACKFileAlreadyExists),
+            0xFF => Ok(Self::NACKGeneral),
             _ => Err(DecodeError::InvalidAck)
         }
     }
@@ -4,6 +4,10 @@
 //! Each "command" also has it's own response associated with it. Commands are implemented using the `Command` trait,
 //! which currently provides a function to encode the implementing structure to a `Vec<u8>` and a function to decode from a Read stream to the implementing structure.
 //! 
+//! With the `serde` feature enabled, the commands, their responses, and the types in [v5] implement `Serialize` and `Deserialize`.
+//! Commands that borrow their data (such as [commands::KVRead] and [commands::FileTransferWrite], along with [commands::AnyCommand])
+//! only implement `Serialize`, since borrowed data can not be deserialized from most formats.
+//! 
 //! V5 devices do not have to be accessed over a serial port, but helper functions are provided for finding and opening serial ports.
 //! Please note that this example may panic and if it succeeds it *will* change the team number on your brain
 //! ```rust
@@ -37,6 +41,10 @@ use crc::Algorithm;
 
 
 
+pub mod simple {
+    pub use crate::commands::{Simple, SimpleResponse};
+}
+
 pub mod extended {
     pub use crate::commands::{Extended, ExtendedResponse};
 }
@@ -46,12 +54,13 @@ pub mod kv {
 }
 
 pub mod system {
-    pub use crate::commands::{V5SystemVersion, GetSystemVersion};
+    pub use crate::commands::{V5SystemVersion, GetSystemVersion, V5SystemStatus, GetSystemStatus, V5SystemFlags, GetSystemFlags};
 
     pub use crate::v5::{
         V5BrainFlags,
         V5ControllerFlags,
-        VexProductType
+        VexProductType,
+        SystemVersion
     };
 }
 
@@ -69,7 +78,13 @@ pub mod file {
         FileTransferRead as FTRead,
         FileTransferSetLink as FTSetLink,
         FileTransferWrite as FTWrite,
-        GetFileMetadataByName
+        GetFileMetadataByName,
+        GetDirectoryCount,
+        GetFileMetadataByIndex,
+        EraseFile,
+        ExecuteFile,
+        encode_file_name,
+        decode_file_name
     };
 
     pub use crate::v5::{
@@ -80,12 +95,55 @@ pub mod file {
         FileTransferType as FTType,
         FileTransferComplete as FTComplete,
         FileMetadataByName,
+        FileMetadataByIndex,
+        FileInfo,
     };
 }
 
 /// Vex uses CRC16/XMODEM as the CRC16.
 pub const VEX_CRC16: Algorithm<u16> = crc::CRC_16_XMODEM;
 
+/// Returns true if the CRC16 at the end of an extended packet is valid.
+/// 
+/// `packet` is the entire packet, from the first byte of the header to the last byte of the big endian CRC16.
+/// Running [VEX_CRC16] over a packet that ends with its own CRC gives a residue of zero, so this only has to check
+/// that the checksum of the whole packet is zero.
+/// 
+/// # Examples
+/// 
+/// ```rust
+/// use vexv5_serial::commands::{Command, GetSystemStatus};
+/// 
+/// let (_, mut packet) = GetSystemStatus().encode_request().unwrap();
+/// assert!(vexv5_serial::crc16_valid(&packet));
+/// 
+/// // Corrupting any byte invalidates the CRC
+/// packet[5] ^= 0x01;
+/// assert!(!vexv5_serial::crc16_valid(&packet));
+/// ```
+pub fn crc16_valid(packet: &[u8]) -> bool {
+    crc::Crc::<u16>::new(&VEX_CRC16).checksum(packet) == 0
+}
+
+/// Rounds a length up to the next multiple of four.
+/// 
+/// File transfer reads and writes must be padded to four byte boundaries, or the brain will NACK them with
+/// [errors::VexACKType::NACKLengthNotPaddedTo4]. Lengths that are already a multiple of four are left unchanged.
+/// 
+/// # Examples
+/// 
+/// ```rust
+/// use vexv5_serial::align4;
+/// 
+/// assert_eq!(align4(0), 0);
+/// assert_eq!(align4(1), 4);
+/// assert_eq!(align4(4), 4);
+/// assert_eq!(align4(5), 8);
+/// ```
+pub fn align4(n: usize) -> usize {
+    (n + 3) & !3
+}
+
 /// Vex uses a CRC32 that I found on page 6 of this document: 
 /// <https://www.matec-conferences.org/articles/matecconf/pdf/2016/11/matecconf_tomsk2016_04001.pdf>
 /// I literally just discovered it by guessing and checking against the PROS implementation.
@@ -98,4 +156,32 @@ pub const VEX_CRC32: Algorithm<u32> = Algorithm {
     check: 0x89A1897F,
     residue: 0x00000000,
     width: 32,
-};
\ No newline at end of file
+};
+
+/// Verifies that the [crc] crate produces the expected check values for [VEX_CRC16] and [VEX_CRC32].
+/// 
+/// The check value is the checksum of the ascii string `123456789`. If the parameters of either algorithm
+/// were to silently change, every packet and file transfer would be corrupted, so this is a cheap sanity check
+/// to run in debug builds or tests.
+/// 
+/// # Panics
+/// 
+/// Panics if either checksum does not match its check value.
+#[doc(hidden)]
+pub fn verify_crc_constants() {
+    // CRC16/XMODEM has a check value of 0x31C3
+    let crc16 = crc::Crc::<u16>::new(&VEX_CRC16).checksum(b"123456789");
+    assert_eq!(crc16, 0x31C3, "VEX_CRC16 check value does not match");
+
+    // The V5 CRC32 has a check value of 0x89A1897F
+    let crc32 = crc::Crc::<u32>::new(&VEX_CRC32).checksum(b"123456789");
+    assert_eq!(crc32, 0x89A1897F, "VEX_CRC32 check value does not match");
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn crc_constants_match_their_check_values() {
+        super::verify_crc_constants();
+    }
+}
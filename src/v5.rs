@@ -9,6 +9,7 @@ use bitflags::bitflags;
 /// * [V5ControllerChannel::Pit] - Used when controlling the robot outside of a competition match
 /// * [V5ControllerChannel::Download] - Used when wirelessly uploading/downloading data to/from the V5 Brain
 #[derive(Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum V5ControllerChannel {
     /// Used when controlling the robot outside of a competition match
     Pit = 0x00,
@@ -25,6 +26,7 @@ pub enum V5ControllerChannel {
 /// * [VexProductType::V5Brain] - Represents a V5 Robot Brain
 /// * [VexProductType::V5Controller] - Represents a V5 Robot Controller
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum VexProductType {
     /// Represents a V5 Robot Brain
     V5Brain(V5BrainFlags),
@@ -33,6 +35,31 @@ pub enum VexProductType {
 }
 
 
+impl VexProductType {
+    /// Returns true if this is a controller that is connected to a brain over VEXLink.
+    /// 
+    /// Only [V5ControllerFlags::CONNECTED_WIRELESS] is checked, so a controller that reports both it and
+    /// [V5ControllerFlags::CONNECTED_CABLE] is still wireless. Commands reach the brain through such a controller
+    /// only once it is on the download channel, and its user port can not be written to.
+    /// 
+    /// # Examples
+    /// 
+    /// ```rust
+    /// use vexv5_serial::v5::{VexProductType, V5ControllerFlags, V5BrainFlags};
+    /// 
+    /// assert!(VexProductType::V5Controller(V5ControllerFlags::CONNECTED_WIRELESS).is_wireless_controller());
+    /// assert!(VexProductType::V5Controller(V5ControllerFlags::CONNECTED_WIRELESS | V5ControllerFlags::CONNECTED_CABLE).is_wireless_controller());
+    /// assert!(!VexProductType::V5Controller(V5ControllerFlags::CONNECTED_CABLE).is_wireless_controller());
+    /// assert!(!VexProductType::V5Brain(V5BrainFlags::NONE).is_wireless_controller());
+    /// ```
+    pub fn is_wireless_controller(&self) -> bool {
+        match self {
+            VexProductType::V5Controller(flags) => flags.contains(V5ControllerFlags::CONNECTED_WIRELESS),
+            VexProductType::V5Brain(_) => false,
+        }
+    }
+}
+
 impl From<VexProductType> for u8 {
     /// Converts the VexProductType to a u8 usable in the serial protocol.
     /// 
@@ -69,6 +96,7 @@ bitflags!{
     /// 
     /// # Members
     /// * [V5BrainFlags::NONE] - There are no documented flags for the v5 brain. Testing will need to be done to determine the actual flags.
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct V5BrainFlags: u8 {
         /// There are no documented flags for the v5 brain. Testing will need to be done to determine the actual flags.
         const NONE = 0x0;
@@ -79,6 +107,7 @@ bitflags!{
     /// * [V5ControllerFlags::NONE] - Represents that no flags are set
     /// * [V5ControllerFlags::CONNECTED_CABLE] - Bit 1 is set when the controller is connected over a cable to the V5 Brain
     /// * [V5ControllerFlags::CONNECTED_WIRELESS] - Bit 2 is set when the controller is connected over VEXLink to the V5 Brain.
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct V5ControllerFlags: u8 {
         /// Represents that no flags are set
         const NONE = 0x0;
@@ -89,6 +118,53 @@ bitflags!{
     }
 }
 
+/// The version of VexOS running on a V5 device, decoded from the five version bytes
+/// returned by [crate::commands::GetSystemVersion].
+/// 
+/// The meaning of these bytes is not documented by Vex, so the field names are a best guess based on
+/// how the version is displayed on the brain.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SystemVersion {
+    /// The major version
+    pub major: u8,
+    /// The minor version
+    pub minor: u8,
+    /// The patch version
+    pub patch: u8,
+    /// The build number
+    pub build: u8,
+    /// The beta number. This appears to be zero on release firmware.
+    pub beta: u8,
+}
+
+impl From<(u8, u8, u8, u8, u8)> for SystemVersion {
+    /// Converts the raw version tuple into a [SystemVersion]
+    fn from(v: (u8, u8, u8, u8, u8)) -> Self {
+        Self {
+            major: v.0,
+            minor: v.1,
+            patch: v.2,
+            build: v.3,
+            beta: v.4,
+        }
+    }
+}
+
+impl std::fmt::Display for SystemVersion {
+    /// Formats the version as `major.minor.patch-buildNN`
+    /// 
+    /// # Example
+    /// ```rust
+    /// use vexv5_serial::v5::SystemVersion;
+    /// 
+    /// assert_eq!(SystemVersion::from((1, 1, 2, 5, 0)).to_string(), "1.1.2-build05");
+    /// ```
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}-build{:02}", self.major, self.minor, self.patch, self.build)
+    }
+}
+
 
 // # File Transfer structures
 // These structures are used during file transfers between the brain and the host
@@ -102,8 +178,9 @@ bitflags!{
 /// * [FileTransferFunction::Upload] - Specifies that a file is being uploaded/written to the brain
 /// * [FileTransferFunction::Download] - Specifies that a file is being downloaded/read from the brain.
 #[repr(u8)]
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FileTransferFunction {
     /// Specifies that a file is being uploaded/written to the brain
     Upload = 0x01,
@@ -119,13 +196,77 @@ pub enum FileTransferFunction {
 /// * [FileTransferTarget::Screen] - The memory accessed when taking a screen capture from the brain.
 #[repr(u8)]
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FileTransferTarget {
     /// The flash memory on the robot brain where most program files are stored
     Flash = 0x01,
     /// The memory accessed when taking a screen capture from the brain.
+    /// 
+    /// Only downloads from this target are known to work. VexOS has no documented way to upload
+    /// pixel data to the screen, so drawing to it has to be done by a user program.
     Screen = 0x02,
 }
 
+/// The format of the pixels read from [FileTransferTarget::Screen]
+/// 
+/// # Variants
+/// 
+/// * [PixelFormat::Xrgb8888] - Each pixel is a little endian u32 of the form `0x00RRGGBB`, so its bytes are blue, green, red, then an unused byte
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PixelFormat {
+    /// Each pixel is a little endian u32 of the form `0x00RRGGBB`, so its bytes are blue, green, red, then an unused byte
+    Xrgb8888,
+}
+
+impl PixelFormat {
+    /// Returns the number of bytes used by each pixel
+    pub fn bytes_per_pixel(&self) -> u8 {
+        match self {
+            PixelFormat::Xrgb8888 => 4,
+        }
+    }
+}
+
+/// The size and layout of the brain's display
+/// 
+/// The brain does not report these, so they are the known values for the V5 brain in [DisplayInfo::V5_BRAIN].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DisplayInfo {
+    /// The width of the visible display in pixels
+    pub width: u16,
+    /// The height of the visible display in pixels
+    pub height: u16,
+    /// The number of pixels in each row of the memory read from [FileTransferTarget::Screen],
+    /// which is wider than the visible display
+    pub stride: u16,
+    /// The format of each pixel
+    pub format: PixelFormat,
+}
+
+impl DisplayInfo {
+    /// The display of the V5 brain, which is 480x272 pixels in [PixelFormat::Xrgb8888], stored in rows of 512 pixels
+    pub const V5_BRAIN: DisplayInfo = DisplayInfo {
+        width: 480,
+        height: 272,
+        stride: 512,
+        format: PixelFormat::Xrgb8888,
+    };
+
+    /// Returns the number of bytes read from [FileTransferTarget::Screen] to capture the whole display
+    /// 
+    /// # Example
+    /// ```rust
+    /// use vexv5_serial::v5::DisplayInfo;
+    /// 
+    /// assert_eq!(DisplayInfo::V5_BRAIN.capture_len(), 512 * 272 * 4);
+    /// ```
+    pub fn capture_len(&self) -> u32 {
+        self.stride as u32 * self.height as u32 * self.format.bytes_per_pixel() as u32
+    }
+}
+
 /// The VID of a file transfer
 /// 
 /// This appears to simply be metadata on what software wrote the file, however I am not entirely sure. To be safe, use User, as it appears to work.
@@ -137,8 +278,12 @@ pub enum FileTransferTarget {
 /// * [FileTransferVID::PROS] - The VID used by Purdue Robotics Operating System
 /// * [FileTransferVID::MW] - I am unsure which software uses the acronym MW, however this VID is used by it.
 /// * [FileTransferVID::Other] - Allows specifying custom VIDs.
+/// 
+/// Because of the [FileTransferVID::Other] variant, this can not be cast with `as u8`.
+/// Use [FileTransferVID::to_u8] (or [u8::from]) when encoding a VID so that custom VIDs are kept.
 #[repr(u8)]
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FileTransferVID {
     /// I am unsure what exactly User and System are intended to be used for, however vexrs uses the User variant when doing file operations, as it appears to work.
     User = 1,
@@ -186,6 +331,16 @@ impl FileTransferVID {
     ///     * `24` == [FileTransferVID::PROS]
     ///     * `32` == [FileTransferVID::MW]
     ///     * `_`  == [FileTransferVID::Other(_)]
+    /// 
+    /// # Examples
+    /// 
+    /// ```rust
+    /// use vexv5_serial::v5::FileTransferVID;
+    /// 
+    /// // Custom VIDs round-trip through their u8 value
+    /// assert_eq!(FileTransferVID::from_u8(FileTransferVID::Other(42).to_u8()), FileTransferVID::Other(42));
+    /// assert_eq!(FileTransferVID::PROS.to_u8(), 24);
+    /// ```
     pub fn to_u8(self) -> u8 {
         match self {
             FileTransferVID::User => 1,
@@ -198,17 +353,47 @@ impl FileTransferVID {
     }
 }
 
+impl From<u8> for FileTransferVID {
+    /// Converts a [u8] to a [FileTransferVID] using [FileTransferVID::from_u8]
+    fn from(v: u8) -> Self {
+        Self::from_u8(v)
+    }
+}
+
+impl From<FileTransferVID> for u8 {
+    /// Converts a [FileTransferVID] to a [u8] using [FileTransferVID::to_u8]
+    fn from(vid: FileTransferVID) -> u8 {
+        vid.to_u8()
+    }
+}
+
 bitflags! {
     /// Options in a file transfer
     /// 
+    /// The same byte is used by several commands, and each bit only has an effect on the operations listed below.
+    /// In every command that takes options (file transfer init, set link, metadata, and erase), the options field is exactly one byte wide,
+    /// matching the `B` fields in PROS-CLI's packet formats.
+    /// 
     /// # Members
     /// * [FileTransferOptions::NONE] - Represents that no options are set
     /// * [FileTransferOptions::OVERWRITE] - Bit 1 is set when the file should be overwritten by the current operation.
+    /// * [FileTransferOptions::ERASE_ALL] - Bit 8 is set when erasing a file should also erase every file linked to it.
+    /// 
+    /// Executing a file gives bit 8 a different meaning, so it has its own constant in [crate::commands::ExecuteFile::STOP_PROGRAM].
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct FileTransferOptions: u8 {
         /// Represents that no options are set
         const NONE = 0x0;
         /// Bit 1 is set when the file should be overwritten by the current operation.
+        /// 
+        /// Used when initializing an upload. Without it, uploading to an existing file NACKs with
+        /// [crate::errors::VexACKType::NACKFileAlreadyExists].
         const OVERWRITE = 0b1;
+        /// Bit 8 is set when erasing a file should also erase every file linked to it.
+        /// 
+        /// Used when erasing a file. This matches the `erase_all` flag of PROS-CLI's `erase_file`.
+        /// The same bit means [crate::commands::ExecuteFile::STOP_PROGRAM] when executing a file.
+        const ERASE_ALL = 0b1000_0000;
     }
 
     
@@ -223,6 +408,7 @@ bitflags! {
 /// * [FileTransferType::Other] - Any other file type, including custom user types
 #[repr(u8)]
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FileTransferType {
     Bin,
     Ini,
@@ -262,6 +448,7 @@ impl FileTransferType {
 /// * [FileTransferComplete::RunProgram] - Runs the uploaded program when the transfer is complete.
 /// * [FileTransferComplete::ShowRunScreen] - Shows the program run screen when the transfer is complete.
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FileTransferComplete {
     DoNothing = 0,
     RunProgram = 1,
@@ -270,6 +457,7 @@ pub enum FileTransferComplete {
 
 /// File metadata returned when requesting file metadata by index
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FileMetadataByIndex {
     /// The index of the file
     pub idx: u8,
@@ -289,8 +477,21 @@ pub struct FileMetadataByIndex {
     pub name: [u8; 24],
 }
 
+impl FileMetadataByIndex {
+    /// Returns the time the file was last edited, decoded with [v5_timestamp_to_system_time]
+    pub fn timestamp_system_time(&self) -> std::time::SystemTime {
+        v5_timestamp_to_system_time(self.timestamp)
+    }
+
+    /// Returns the version of the file as `(major, minor, patch, build)`, decoded with [unpack_file_version]
+    pub fn version_tuple(&self) -> (u8, u8, u8, u8) {
+        unpack_file_version(self.version)
+    }
+}
+
 /// File metadata returned when requesting file metadata by name
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FileMetadataByName {
     /// The VID of the linked file
     pub linked_vid: FileTransferVID,
@@ -308,4 +509,92 @@ pub struct FileMetadataByName {
     pub version: u32,
     /// The filename of the linked file
     pub linked_filename: [u8; 24],
-}
\ No newline at end of file
+}
+
+impl FileMetadataByName {
+    /// Returns the time the file was last edited, decoded with [v5_timestamp_to_system_time]
+    pub fn timestamp_system_time(&self) -> std::time::SystemTime {
+        v5_timestamp_to_system_time(self.timestamp)
+    }
+
+    /// Returns the version of the file as `(major, minor, patch, build)`, decoded with [unpack_file_version]
+    pub fn version_tuple(&self) -> (u8, u8, u8, u8) {
+        unpack_file_version(self.version)
+    }
+}
+
+/// The number of seconds between the unix epoch and the epoch used by V5 timestamps (January 1st, 2000 UTC)
+pub const V5_EPOCH_OFFSET: u64 = 946684800;
+
+/// Converts a V5 timestamp (seconds since January 1st, 2000 UTC) into a [std::time::SystemTime]
+pub fn v5_timestamp_to_system_time(timestamp: u32) -> std::time::SystemTime {
+    std::time::UNIX_EPOCH + std::time::Duration::from_secs(V5_EPOCH_OFFSET + timestamp as u64)
+}
+
+/// Converts a [std::time::SystemTime] into a V5 timestamp (seconds since January 1st, 2000 UTC).
+/// 
+/// Times before the V5 epoch become zero, and times past the end of the range become [u32::MAX].
+pub fn system_time_to_v5_timestamp(time: std::time::SystemTime) -> u32 {
+    let unix_seconds = time.duration_since(std::time::UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+
+    unix_seconds.saturating_sub(V5_EPOCH_OFFSET).min(u32::MAX as u64) as u32
+}
+
+/// Unpacks a packed file version, such that 0x01020304 == (1, 2, 3, 4)
+pub fn unpack_file_version(version: u32) -> (u8, u8, u8, u8) {
+    let v = version.to_be_bytes();
+    (v[0], v[1], v[2], v[3])
+}
+
+/// Information about a file, decoded from either [FileMetadataByIndex] or [FileMetadataByName]
+/// so that file listings can use a single type.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FileInfo {
+    /// The name of the file
+    pub name: String,
+    /// The type of the file
+    pub file_type: FileTransferType,
+    /// The length of the file
+    pub length: u32,
+    /// The address the file should be loaded at
+    pub addr: u32,
+    /// The crc32 of the file according to [crate::VEX_CRC32].
+    pub crc: u32,
+    /// The time the file was last edited
+    pub timestamp: std::time::SystemTime,
+    /// The version of the file
+    pub version: (u8, u8, u8, u8),
+}
+
+impl FileInfo {
+    /// Creates a [FileInfo] from [FileMetadataByName].
+    /// 
+    /// The metadata returned by name does not include the name of the file itself
+    /// (`linked_filename` is the name of the linked file), so the name that was requested must be provided.
+    pub fn from_metadata_by_name(name: &str, metadata: FileMetadataByName) -> Self {
+        Self {
+            name: name.to_string(),
+            file_type: metadata.file_type,
+            length: metadata.length,
+            addr: metadata.addr,
+            crc: metadata.crc,
+            timestamp: metadata.timestamp_system_time(),
+            version: metadata.version_tuple(),
+        }
+    }
+}
+
+impl From<FileMetadataByIndex> for FileInfo {
+    fn from(metadata: FileMetadataByIndex) -> Self {
+        Self {
+            name: crate::commands::decode_file_name(&metadata.name),
+            file_type: metadata.file_type,
+            length: metadata.length,
+            addr: metadata.addr,
+            crc: metadata.crc,
+            timestamp: metadata.timestamp_system_time(),
+            version: metadata.version_tuple(),
+        }
+    }
+}